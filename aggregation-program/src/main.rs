@@ -0,0 +1,33 @@
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use alloy::sol_types::SolValue;
+use primitives::aggregation::aggregate;
+use primitives::types::ProofOutputs;
+use sha2::{Digest, Sha256};
+
+pub fn main() {
+    // Read in the verifying key of the Blobstream program and the public values of each range
+    // proof, in order. The compressed range proofs themselves are supplied by the host.
+    let range_vkey = sp1_zkvm::io::read::<[u32; 8]>();
+    let range_public_values = sp1_zkvm::io::read::<Vec<Vec<u8>>>();
+
+    // Verify each range proof and decode its outputs. The outputs are decoded as a prefix without
+    // validation, so the `ExtendedProofOutputs` of a program built with the `app-hash` feature,
+    // which start with the `ProofOutputs`, can be aggregated too.
+    let ranges: Vec<ProofOutputs> = range_public_values
+        .iter()
+        .map(|public_values| {
+            let public_values_digest: [u8; 32] = Sha256::digest(public_values).into();
+            sp1_zkvm::lib::verify::verify_sp1_proof(&range_vkey, &public_values_digest);
+            ProofOutputs::abi_decode(public_values, false).unwrap()
+        })
+        .collect();
+
+    // Check the ranges chain together and aggregate their commitments.
+    let aggregation_outputs =
+        aggregate(&range_vkey, &ranges).unwrap_or_else(|err| panic!("{}", err));
+
+    // ABI encode the aggregation outputs to bytes and commit them to the zkVM.
+    sp1_zkvm::io::commit_slice(&aggregation_outputs.abi_encode());
+}
@@ -35,6 +35,8 @@ interface ISP1Blobstream {
 
     /// @notice Validator bitmap associated with the proof from trustedBlock to targetBlock. The uint256
     /// is encoded as a bitmap of the validators from the trustedBlock that signed off on the new header.
+    /// The validator set is identified by the `trustedValidatorsHash` committed in the proof's public
+    /// values, which is the `validators_hash` of the trusted header.
     /// @param trustedBlock The trusted block of the block range.
     /// @param targetBlock The target block of the block range.
     /// @param validatorBitmap The validator bitmap for the block range.
@@ -0,0 +1,18 @@
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use alloy::sol_types::SolValue;
+use primitives::evidence::{execute_evidence, EvidenceInputs};
+
+pub fn main() {
+    // Read in the evidence inputs. Note: Use a slice, as bincode is unable to deserialize protobuf.
+    let evidence_inputs_vec = sp1_zkvm::io::read_vec();
+    let evidence_inputs: EvidenceInputs = serde_cbor::from_slice(&evidence_inputs_vec).unwrap();
+
+    // Verify both conflicting blocks from the trusted block and find the double signers.
+    let evidence_outputs =
+        execute_evidence(&evidence_inputs).unwrap_or_else(|err| panic!("{}", err));
+
+    // ABI encode the evidence outputs to bytes and commit them to the zkVM.
+    sp1_zkvm::io::commit_slice(&evidence_outputs.abi_encode());
+}
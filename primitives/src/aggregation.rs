@@ -0,0 +1,209 @@
+use alloy::primitives::B256;
+use alloy::sol;
+
+use crate::commitment::DataCommitmentScheme;
+use crate::error::BlobstreamError;
+use crate::types::ProofOutputs;
+use crate::CommitmentScheme;
+
+sol! {
+    /// The public values committed by the aggregation program, which verifies a chain of
+    /// consecutive Blobstream range proofs.
+    ///
+    /// The data commitment is the root of the commitment scheme's Merkle tree whose leaves are the
+    /// data commitments of the ranges, in order. The chain ID and verification params hashes are
+    /// shared by every range.
+    ///
+    /// The range vkey hash identifies the Blobstream program the range proofs were verified
+    /// against, as the big-endian bytes of its `vk.hash_u32()` words. Note that this is not the
+    /// `vk.bytes32()` value stored as `blobstreamProgramVkey` in the contract, which is a different
+    /// hash of the same key that the zkVM cannot compute cheaply. To check it on chain, store the
+    /// `hash_u32` form of the Blobstream program's key alongside the aggregation program's key.
+    ///
+    /// Like `ProofOutputs`, the trusted header time of the first range, the target header time of
+    /// the last range and the trusting period let consumers check that the trusted header was still
+    /// within the trusting period.
+    struct AggregationOutputs {
+        bytes32 trustedHeaderHash;
+        bytes32 targetHeaderHash;
+        bytes32 dataCommitment;
+        uint64 trustedBlock;
+        uint64 targetBlock;
+        bytes32 rangeVkeyHash;
+        uint64 rangeCount;
+        bytes32 chainIdHash;
+        bytes32 verificationParamsHash;
+        uint64 trustedHeaderTime;
+        uint64 targetHeaderTime;
+        uint64 trustingPeriod;
+    }
+}
+
+/// Aggregate the outputs of consecutive range proofs, checking that each range starts at the target
+/// of the previous one. The aggregation program calls this after verifying the range proofs.
+pub fn aggregate(
+    range_vkey: &[u32; 8],
+    ranges: &[ProofOutputs],
+) -> Result<AggregationOutputs, BlobstreamError> {
+    let (first, last) = match (ranges.first(), ranges.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Err(BlobstreamError::EmptyAggregation),
+    };
+
+    for (index, pair) in ranges.windows(2).enumerate() {
+        let (prev, next) = (&pair[0], &pair[1]);
+        if prev.targetHeaderHash != next.trustedHeaderHash || prev.targetBlock != next.trustedBlock
+        {
+            return Err(BlobstreamError::RangeNotContiguous { index: index + 1 });
+        }
+        if prev.chainIdHash != next.chainIdHash
+            || prev.verificationParamsHash != next.verificationParamsHash
+        {
+            return Err(BlobstreamError::RangeParamsMismatch { index: index + 1 });
+        }
+    }
+
+    let data_commitments: Vec<B256> = ranges.iter().map(|range| range.dataCommitment).collect();
+    let range_vkey_hash: Vec<u8> = range_vkey
+        .iter()
+        .flat_map(|word| word.to_be_bytes())
+        .collect();
+
+    Ok(AggregationOutputs {
+        trustedHeaderHash: first.trustedHeaderHash,
+        targetHeaderHash: last.targetHeaderHash,
+        dataCommitment: B256::from(CommitmentScheme::merkle_root(&data_commitments)),
+        trustedBlock: first.trustedBlock,
+        targetBlock: last.targetBlock,
+        rangeVkeyHash: B256::from_slice(&range_vkey_hash),
+        rangeCount: ranges.len() as u64,
+        chainIdHash: first.chainIdHash,
+        verificationParamsHash: first.verificationParamsHash,
+        trustedHeaderTime: first.trustedHeaderTime,
+        targetHeaderTime: last.targetHeaderTime,
+        trustingPeriod: first.trustingPeriod,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+
+    use super::*;
+
+    /// The outputs of a range proof from trusted_block to target_block, with header hashes and
+    /// data commitments derived from the heights.
+    fn test_range(trusted_block: u64, target_block: u64) -> ProofOutputs {
+        let header_hash = |height: u64| B256::from(U256::from(height));
+        ProofOutputs {
+            trustedHeaderHash: header_hash(trusted_block),
+            targetHeaderHash: header_hash(target_block),
+            dataCommitment: B256::from(U256::from(trusted_block << 32 | target_block)),
+            trustedBlock: trusted_block,
+            targetBlock: target_block,
+            validatorBitmap: U256::ZERO,
+            trustedHeaderTime: trusted_block,
+            targetHeaderTime: target_block,
+            trustingPeriod: 60,
+            chainIdHash: B256::repeat_byte(1),
+            verificationParamsHash: B256::repeat_byte(2),
+            validatorCount: 0,
+            validatorBitmapHash: B256::ZERO,
+            trustedValidatorsHash: B256::ZERO,
+            targetNextValidatorsHash: B256::ZERO,
+            headerCommitment: B256::ZERO,
+            rangeLength: target_block - trusted_block,
+            proofMode: 0,
+            totalVotingPower: 0,
+            signedVotingPower: 0,
+            targetDataRoot: B256::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_contiguous() {
+        let ranges = [test_range(1, 5), test_range(5, 8), test_range(8, 20)];
+        let range_vkey = [1, 2, 3, 4, 5, 6, 7, 8];
+        let outputs = aggregate(&range_vkey, &ranges).unwrap();
+
+        assert_eq!(outputs.trustedHeaderHash, ranges[0].trustedHeaderHash);
+        assert_eq!(outputs.targetHeaderHash, ranges[2].targetHeaderHash);
+        assert_eq!(outputs.trustedBlock, 1);
+        assert_eq!(outputs.targetBlock, 20);
+        assert_eq!(outputs.rangeCount, 3);
+        assert_eq!(outputs.trustedHeaderTime, 1);
+        assert_eq!(outputs.targetHeaderTime, 20);
+        assert_eq!(outputs.trustingPeriod, 60);
+        assert_eq!(outputs.chainIdHash, B256::repeat_byte(1));
+        assert_eq!(outputs.verificationParamsHash, B256::repeat_byte(2));
+
+        let data_commitments: Vec<B256> = ranges.iter().map(|range| range.dataCommitment).collect();
+        assert_eq!(
+            outputs.dataCommitment,
+            B256::from(CommitmentScheme::merkle_root(&data_commitments))
+        );
+
+        let mut range_vkey_hash = [0u8; 32];
+        for (index, word) in range_vkey.iter().enumerate() {
+            range_vkey_hash[index * 4..index * 4 + 4].copy_from_slice(&word.to_be_bytes());
+        }
+        assert_eq!(outputs.rangeVkeyHash, B256::from(range_vkey_hash));
+    }
+
+    #[test]
+    fn test_aggregate_single_range() {
+        let range = test_range(1, 5);
+        let outputs = aggregate(&[0; 8], &[range.clone()]).unwrap();
+        assert_eq!(outputs.trustedBlock, 1);
+        assert_eq!(outputs.targetBlock, 5);
+        assert_eq!(outputs.rangeCount, 1);
+        assert_eq!(
+            outputs.dataCommitment,
+            B256::from(CommitmentScheme::merkle_root(&[range.dataCommitment]))
+        );
+    }
+
+    #[test]
+    fn test_aggregate_gap() {
+        let ranges = [test_range(1, 5), test_range(5, 8), test_range(9, 20)];
+        assert!(matches!(
+            aggregate(&[0; 8], &ranges),
+            Err(BlobstreamError::RangeNotContiguous { index: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_header_hash_mismatch() {
+        let mut ranges = [test_range(1, 5), test_range(5, 8)];
+        ranges[1].trustedHeaderHash = B256::repeat_byte(9);
+        assert!(matches!(
+            aggregate(&[0; 8], &ranges),
+            Err(BlobstreamError::RangeNotContiguous { index: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_params_mismatch() {
+        let mut ranges = [test_range(1, 5), test_range(5, 8)];
+        ranges[1].verificationParamsHash = B256::repeat_byte(9);
+        assert!(matches!(
+            aggregate(&[0; 8], &ranges),
+            Err(BlobstreamError::RangeParamsMismatch { index: 1 })
+        ));
+
+        let mut ranges = [test_range(1, 5), test_range(5, 8)];
+        ranges[1].chainIdHash = B256::repeat_byte(9);
+        assert!(matches!(
+            aggregate(&[0; 8], &ranges),
+            Err(BlobstreamError::RangeParamsMismatch { index: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_empty() {
+        assert!(matches!(
+            aggregate(&[0; 8], &[]),
+            Err(BlobstreamError::EmptyAggregation)
+        ));
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+use std::ops::Add;
+
+use alloy::primitives::U256;
+use sha2::{Digest, Sha256};
+use tendermint::account;
+use tendermint_light_client_verifier::types::LightBlock;
+
+/// The addresses of the validators that signed off on the light block's header.
+pub(crate) fn get_commit_signers(light_block: &LightBlock) -> HashSet<account::Id> {
+    light_block
+        .signed_header
+        .commit
+        .signatures
+        .iter()
+        .filter(|sig| sig.is_commit())
+        .filter_map(|sig| sig.validator_address())
+        .collect()
+}
+
+/// Construct a bitmap of the intersection of the validators that signed off on the trusted and
+/// target header. Use the order of the validators from the trusted header, with one entry per
+/// validator in the trusted validator set. Equivocates slashing in the case that validators are
+/// malicious.
+pub fn get_validator_bitmap(
+    trusted_light_block: &LightBlock,
+    target_light_block: &LightBlock,
+) -> Vec<bool> {
+    // If a validator has signed off on both headers, add them to the intersection set. Collect the
+    // signers of the target commit first, so the intersection takes linear time.
+    let target_signers = get_commit_signers(target_light_block);
+    let validator_commit_intersection: HashSet<_> = get_commit_signers(trusted_light_block)
+        .into_iter()
+        .filter(|address| target_signers.contains(address))
+        .collect();
+
+    // Construct the validator bitmap.
+    trusted_light_block
+        .validators
+        .validators()
+        .iter()
+        .map(|validator| validator_commit_intersection.contains(&validator.address))
+        .collect()
+}
+
+/// The addresses of the validators in the light block's own validator set that signed off on its
+/// header. Once the light block has been verified, the verifier has checked this validator set
+/// against the header and the signature of every one of these validators.
+fn get_verified_commit_signers(light_block: &LightBlock) -> HashSet<account::Id> {
+    let validators: HashSet<account::Id> = light_block
+        .validators
+        .validators()
+        .iter()
+        .map(|validator| validator.address)
+        .collect();
+    get_commit_signers(light_block)
+        .into_iter()
+        .filter(|address| validators.contains(address))
+        .collect()
+}
+
+/// Construct a bitmap of the validators that signed off on both of two conflicting headers at the
+/// same height, i.e. that double signed. Use the order of the validators from the trusted header,
+/// with one entry per validator in the trusted validator set.
+///
+/// Only signatures of validators in each conflicting block's own validator set are counted, so the
+/// conflicting blocks must have been verified, and the trusted validator set checked against the
+/// trusted header, for every marked signature to have been verified.
+pub fn get_double_sign_bitmap(
+    trusted_light_block: &LightBlock,
+    light_block_a: &LightBlock,
+    light_block_b: &LightBlock,
+) -> Vec<bool> {
+    let signers_a = get_verified_commit_signers(light_block_a);
+    let signers_b = get_verified_commit_signers(light_block_b);
+
+    trusted_light_block
+        .validators
+        .validators()
+        .iter()
+        .map(|validator| {
+            signers_a.contains(&validator.address) && signers_b.contains(&validator.address)
+        })
+        .collect()
+}
+
+/// Split a validator bitmap into U256 words. Bit `i % 256` of word `i / 256` is set if validator `i`
+/// is in the bitmap, so the first word is the bitmap of the first 256 validators.
+pub fn get_validator_bitmap_words(validator_bitmap: &[bool]) -> Vec<U256> {
+    validator_bitmap
+        .chunks(256)
+        .map(|chunk| {
+            let mut word = U256::from(0);
+            for (index, &value) in chunk.iter().enumerate() {
+                if value {
+                    word = word.add(U256::from(1) << index)
+                }
+            }
+            word
+        })
+        .collect()
+}
+
+/// Hash a validator bitmap of any length. The hash is the SHA-256 of the concatenated big-endian
+/// bitmap words, i.e. `sha256(abi.encodePacked(words))` in Solidity.
+pub fn hash_validator_bitmap(validator_bitmap: &[bool]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for word in get_validator_bitmap_words(validator_bitmap) {
+        hasher.update(word.to_be_bytes::<32>());
+    }
+    hasher.finalize().into()
+}
@@ -0,0 +1,270 @@
+use alloy::sol;
+use alloy::sol_types::SolType;
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+use tendermint::{block::Header, Hash};
+
+use crate::error::BlobstreamError;
+
+type DataRootTuple = sol! {
+    tuple(uint64, bytes32)
+};
+
+/// A scheme for committing to the data roots and header hashes of a range of headers. Commitments
+/// are roots of an RFC 6962 style Merkle tree (as used by Tendermint), built from the scheme's leaf
+/// encoding and hash function.
+pub trait DataCommitmentScheme {
+    /// Encode the leaf for the block at the given height with the given data root.
+    fn encode_leaf(height: u64, data_root: [u8; 32]) -> Vec<u8>;
+
+    /// Hash a leaf of the Merkle tree.
+    fn leaf_hash(leaf: &[u8]) -> [u8; 32];
+
+    /// Hash an inner node of the Merkle tree.
+    fn inner_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32];
+
+    /// The root of an empty Merkle tree.
+    fn empty_hash() -> [u8; 32];
+
+    /// Compute the root of the Merkle tree over the leaves.
+    fn merkle_root(leaves: &[impl AsRef<[u8]>]) -> [u8; 32] {
+        match leaves.len() {
+            0 => Self::empty_hash(),
+            1 => Self::leaf_hash(leaves[0].as_ref()),
+            n => {
+                let split_point = get_split_point(n);
+                Self::inner_hash(
+                    &Self::merkle_root(&leaves[..split_point]),
+                    &Self::merkle_root(&leaves[split_point..]),
+                )
+            }
+        }
+    }
+
+    /// Compute the data commitment for the supplied headers, given the hash of each header. Each
+    /// leaf in the Merkle tree is the encoding of the block height and the header's data hash.
+    /// Excludes the last header's data hash from the commitment to avoid overlapping headers
+    /// between commits.
+    ///
+    /// Fails unless the headers form a valid chain, see `validate_header_link`.
+    fn data_commitment(
+        headers: &[&Header],
+        header_hashes: &[Hash],
+    ) -> Result<[u8; 32], BlobstreamError> {
+        let mut encoded_leaves: Vec<Vec<u8>> = Vec::with_capacity(headers.len() - 1);
+        // Loop over all headers except the last one.
+        for i in 0..headers.len() - 1 {
+            let curr_header = headers[i];
+            let next_header = headers[i + 1];
+
+            // Verify the chain of headers is connected.
+            validate_header_link(curr_header, &header_hashes[i], next_header)?;
+
+            let data_hash: [u8; 32] = curr_header
+                .data_hash
+                .and_then(|data_hash| data_hash.as_bytes().try_into().ok())
+                .ok_or(BlobstreamError::MissingDataHash {
+                    height: curr_header.height.value(),
+                })?;
+
+            encoded_leaves.push(Self::encode_leaf(curr_header.height.value(), data_hash));
+        }
+
+        Ok(Self::merkle_root(&encoded_leaves))
+    }
+
+    /// Compute the header commitment from the hashes of the supplied headers: the root of the
+    /// Merkle tree whose leaves are the header hashes. Like the data commitment, excludes the last
+    /// header.
+    fn header_commitment(header_hashes: &[Hash]) -> [u8; 32] {
+        Self::merkle_root(&header_hashes[..header_hashes.len() - 1])
+    }
+}
+
+/// The Blobstream data commitment scheme. Each leaf is an ABI-encoded `DataRootTuple` and the tree
+/// is the SHA-256 Tendermint simple Merkle tree, which is what the Blobstream contracts verify
+/// attestations against.
+pub struct BlobstreamScheme;
+
+impl DataCommitmentScheme for BlobstreamScheme {
+    fn encode_leaf(height: u64, data_root: [u8; 32]) -> Vec<u8> {
+        DataRootTuple::abi_encode(&(height, data_root))
+    }
+
+    fn leaf_hash(leaf: &[u8]) -> [u8; 32] {
+        hash_with_prefix::<Sha256>(0x00, &[leaf])
+    }
+
+    fn inner_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        hash_with_prefix::<Sha256>(0x01, &[left.as_slice(), right.as_slice()])
+    }
+
+    fn empty_hash() -> [u8; 32] {
+        Sha256::digest(b"").into()
+    }
+}
+
+/// A Keccak-256 data commitment scheme, which is cheaper to verify on chains where Keccak-256 is
+/// the native hash function. Each leaf is the packed encoding of the height and data root,
+/// `abi.encodePacked(uint64 height, bytes32 dataRoot)`, and the tree has the same shape and
+/// domain separation as the Tendermint simple Merkle tree.
+pub struct KeccakScheme;
+
+impl DataCommitmentScheme for KeccakScheme {
+    fn encode_leaf(height: u64, data_root: [u8; 32]) -> Vec<u8> {
+        [height.to_be_bytes().as_slice(), data_root.as_slice()].concat()
+    }
+
+    fn leaf_hash(leaf: &[u8]) -> [u8; 32] {
+        hash_with_prefix::<Keccak256>(0x00, &[leaf])
+    }
+
+    fn inner_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        hash_with_prefix::<Keccak256>(0x01, &[left.as_slice(), right.as_slice()])
+    }
+
+    fn empty_hash() -> [u8; 32] {
+        Keccak256::digest(b"").into()
+    }
+}
+
+/// Validate that next_header directly follows curr_header, whose hash is curr_hash, on the same
+/// chain: its height is one more, its time is later, its chain ID is the same and its last block ID
+/// is curr_hash.
+pub fn validate_header_link(
+    curr_header: &Header,
+    curr_hash: &Hash,
+    next_header: &Header,
+) -> Result<(), BlobstreamError> {
+    let height = next_header.height.value();
+    let previous_height = curr_header.height.value();
+    if previous_height.checked_add(1) != Some(height) {
+        return Err(BlobstreamError::NonConsecutiveHeight {
+            height,
+            previous_height,
+        });
+    }
+    if next_header.time <= curr_header.time {
+        return Err(BlobstreamError::NonIncreasingTime { height });
+    }
+    if next_header.chain_id != curr_header.chain_id {
+        return Err(BlobstreamError::ChainIdMismatch {
+            height,
+            expected: curr_header.chain_id.to_string(),
+            found: next_header.chain_id.to_string(),
+        });
+    }
+    let last_block_id = next_header
+        .last_block_id
+        .ok_or(BlobstreamError::MissingLastBlockId { height })?;
+    if *curr_hash != last_block_id.hash {
+        return Err(BlobstreamError::HeaderLinkMismatch { height });
+    }
+    Ok(())
+}
+
+/// Hash the prefix byte followed by the parts.
+fn hash_with_prefix<H: Digest>(prefix: u8, parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = H::new();
+    hasher.update([prefix]);
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&hasher.finalize());
+    hash
+}
+
+/// The largest power of two strictly less than n.
+pub fn get_split_point(n: usize) -> usize {
+    let mut split_point = 1;
+    while split_point * 2 < n {
+        split_point *= 2;
+    }
+    split_point
+}
+
+#[cfg(test)]
+mod tests {
+    use tendermint::{block, Time};
+
+    use crate::types::tests::test_light_block;
+
+    use super::*;
+
+    /// The header at the height and the header after it, linked to it by its hash.
+    fn linked_headers(height: u64) -> (Header, Hash, Header) {
+        let curr_header = test_light_block(height).signed_header.header;
+        let curr_hash = curr_header.hash();
+        let mut next_header = test_light_block(height + 1).signed_header.header;
+        next_header.last_block_id = Some(block::Id {
+            hash: curr_hash,
+            ..next_header.last_block_id.unwrap()
+        });
+        (curr_header, curr_hash, next_header)
+    }
+
+    #[test]
+    fn test_validate_header_link() {
+        let (curr_header, curr_hash, next_header) = linked_headers(2);
+        validate_header_link(&curr_header, &curr_hash, &next_header).unwrap();
+    }
+
+    #[test]
+    fn test_validate_header_link_non_consecutive_height() {
+        let (curr_header, curr_hash, mut next_header) = linked_headers(2);
+        next_header.height = 4u32.into();
+        assert!(matches!(
+            validate_header_link(&curr_header, &curr_hash, &next_header),
+            Err(BlobstreamError::NonConsecutiveHeight {
+                height: 4,
+                previous_height: 2,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_validate_header_link_non_increasing_time() {
+        let (curr_header, curr_hash, mut next_header) = linked_headers(2);
+        next_header.time = curr_header.time;
+        assert!(matches!(
+            validate_header_link(&curr_header, &curr_hash, &next_header),
+            Err(BlobstreamError::NonIncreasingTime { height: 3 })
+        ));
+
+        next_header.time = Time::from_unix_timestamp(0, 0).unwrap();
+        assert!(matches!(
+            validate_header_link(&curr_header, &curr_hash, &next_header),
+            Err(BlobstreamError::NonIncreasingTime { height: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_validate_header_link_chain_id_mismatch() {
+        let (curr_header, curr_hash, mut next_header) = linked_headers(2);
+        next_header.chain_id = "other-chain".parse().unwrap();
+        assert!(matches!(
+            validate_header_link(&curr_header, &curr_hash, &next_header),
+            Err(BlobstreamError::ChainIdMismatch { height: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_header_link_missing_last_block_id() {
+        let (curr_header, curr_hash, mut next_header) = linked_headers(2);
+        next_header.last_block_id = None;
+        assert!(matches!(
+            validate_header_link(&curr_header, &curr_hash, &next_header),
+            Err(BlobstreamError::MissingLastBlockId { height: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_validate_header_link_hash_mismatch() {
+        let (curr_header, _, next_header) = linked_headers(2);
+        assert!(matches!(
+            validate_header_link(&curr_header, &Hash::Sha256([7; 32]), &next_header),
+            Err(BlobstreamError::HeaderLinkMismatch { height: 3 })
+        ));
+    }
+}
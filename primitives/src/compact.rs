@@ -0,0 +1,269 @@
+//! A compact protobuf encoding of `ProofInputs`. Light blocks and headers are carried as their
+//! `tendermint-proto` messages, which are much cheaper to decode in the zkVM than their serde
+//! representations.
+//!
+//! Only what the verifier needs is carried. The peer ID of the light block provider is dropped, and
+//! each distinct validator set is carried once and referenced by index from the light blocks. The
+//! validators and next validators of a block are usually the same set, and consecutive blocks
+//! usually share it too, so most of the validator sets in the inputs are decoded (and their public
+//! keys parsed) only once. The rest of each light block is needed: the verifier hashes the full
+//! header, checks the commit signatures and checks both validator sets against the header.
+
+use prost::Message;
+use tendermint::{
+    block::{signed_header::SignedHeader, Header},
+    node,
+    validator::Set as ValidatorSet,
+};
+use tendermint_light_client_verifier::types::LightBlock;
+use tendermint_proto::v0_34::types::{
+    Header as RawHeader, SignedHeader as RawSignedHeader, ValidatorSet as RawValidatorSet,
+};
+
+use crate::types::{ProofInputs, ProofMode, VerificationParams};
+
+/// A light block without its provider. The validator sets are indices into
+/// `CompactProofInputs::validator_sets`.
+#[derive(Clone, PartialEq, Message)]
+pub struct CompactLightBlock {
+    #[prost(message, optional, tag = "1")]
+    pub signed_header: Option<RawSignedHeader>,
+    #[prost(uint32, tag = "2")]
+    pub validators_index: u32,
+    #[prost(uint32, tag = "3")]
+    pub next_validators_index: u32,
+}
+
+/// The protobuf message for `ProofInputs`.
+#[derive(Clone, PartialEq, Message)]
+pub struct CompactProofInputs {
+    #[prost(message, optional, tag = "1")]
+    pub trusted_light_block: Option<CompactLightBlock>,
+    #[prost(message, optional, tag = "2")]
+    pub target_light_block: Option<CompactLightBlock>,
+    #[prost(message, repeated, tag = "3")]
+    pub headers: Vec<RawHeader>,
+    #[prost(uint64, tag = "4")]
+    pub trust_threshold_numerator: u64,
+    #[prost(uint64, tag = "5")]
+    pub trust_threshold_denominator: u64,
+    #[prost(uint64, tag = "6")]
+    pub trusting_period_secs: u64,
+    #[prost(uint64, tag = "7")]
+    pub clock_drift_secs: u64,
+    #[prost(message, repeated, tag = "8")]
+    pub pivot_light_blocks: Vec<CompactLightBlock>,
+    #[prost(bool, tag = "9")]
+    pub commit_header_hashes: bool,
+    #[prost(uint32, tag = "10")]
+    pub mode: u32,
+    /// The distinct validator sets of the light blocks.
+    #[prost(message, repeated, tag = "11")]
+    pub validator_sets: Vec<RawValidatorSet>,
+}
+
+/// The index of the validator set in the distinct validator sets, adding it if it is new.
+fn validator_set_index<'a>(
+    validator_sets: &mut Vec<&'a ValidatorSet>,
+    set: &'a ValidatorSet,
+) -> u32 {
+    let index = match validator_sets.iter().position(|existing| *existing == set) {
+        Some(index) => index,
+        None => {
+            validator_sets.push(set);
+            validator_sets.len() - 1
+        }
+    };
+    index as u32
+}
+
+/// Encode the light block, adding its validator sets to the distinct validator sets.
+fn encode_light_block<'a>(
+    light_block: &'a LightBlock,
+    validator_sets: &mut Vec<&'a ValidatorSet>,
+) -> CompactLightBlock {
+    CompactLightBlock {
+        signed_header: Some(light_block.signed_header.clone().into()),
+        validators_index: validator_set_index(validator_sets, &light_block.validators),
+        next_validators_index: validator_set_index(validator_sets, &light_block.next_validators),
+    }
+}
+
+/// Decode the light block, cloning its validator sets from the decoded distinct validator sets.
+fn decode_light_block(
+    light_block: CompactLightBlock,
+    validator_sets: &[ValidatorSet],
+) -> Result<LightBlock, String> {
+    let signed_header = SignedHeader::try_from(
+        light_block
+            .signed_header
+            .ok_or("missing light block signed header")?,
+    )
+    .map_err(|err| err.to_string())?;
+    let validator_set = |index: u32| {
+        validator_sets
+            .get(index as usize)
+            .cloned()
+            .ok_or(format!("validator set index {} out of range", index))
+    };
+    // The provider is not used by the verifier.
+    Ok(LightBlock::new(
+        signed_header,
+        validator_set(light_block.validators_index)?,
+        validator_set(light_block.next_validators_index)?,
+        node::Id::new([0; 20]),
+    ))
+}
+
+impl From<&ProofInputs> for CompactProofInputs {
+    fn from(inputs: &ProofInputs) -> Self {
+        let params = &inputs.verification_params;
+        let mut validator_sets = Vec::new();
+        let trusted_light_block =
+            encode_light_block(&inputs.trusted_light_block, &mut validator_sets);
+        let target_light_block =
+            encode_light_block(&inputs.target_light_block, &mut validator_sets);
+        let pivot_light_blocks = inputs
+            .pivot_light_blocks
+            .iter()
+            .map(|light_block| encode_light_block(light_block, &mut validator_sets))
+            .collect();
+        Self {
+            trusted_light_block: Some(trusted_light_block),
+            target_light_block: Some(target_light_block),
+            headers: inputs.headers.iter().cloned().map(Into::into).collect(),
+            trust_threshold_numerator: params.trust_threshold_numerator,
+            trust_threshold_denominator: params.trust_threshold_denominator,
+            trusting_period_secs: params.trusting_period_secs,
+            clock_drift_secs: params.clock_drift_secs,
+            pivot_light_blocks,
+            commit_header_hashes: inputs.commit_header_hashes,
+            mode: inputs.mode.id().into(),
+            validator_sets: validator_sets
+                .into_iter()
+                .map(|set| set.clone().into())
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<CompactProofInputs> for ProofInputs {
+    type Error = String;
+
+    fn try_from(inputs: CompactProofInputs) -> Result<Self, Self::Error> {
+        let validator_sets: Vec<ValidatorSet> = inputs
+            .validator_sets
+            .into_iter()
+            .map(|set| ValidatorSet::try_from(set).map_err(|err| err.to_string()))
+            .collect::<Result<_, _>>()?;
+        Ok(ProofInputs {
+            trusted_light_block: decode_light_block(
+                inputs
+                    .trusted_light_block
+                    .ok_or("missing trusted light block")?,
+                &validator_sets,
+            )?,
+            target_light_block: decode_light_block(
+                inputs
+                    .target_light_block
+                    .ok_or("missing target light block")?,
+                &validator_sets,
+            )?,
+            headers: inputs
+                .headers
+                .into_iter()
+                .map(|header| Header::try_from(header).map_err(|err| err.to_string()))
+                .collect::<Result<_, _>>()?,
+            verification_params: VerificationParams {
+                trust_threshold_numerator: inputs.trust_threshold_numerator,
+                trust_threshold_denominator: inputs.trust_threshold_denominator,
+                trusting_period_secs: inputs.trusting_period_secs,
+                clock_drift_secs: inputs.clock_drift_secs,
+            },
+            pivot_light_blocks: inputs
+                .pivot_light_blocks
+                .into_iter()
+                .map(|light_block| decode_light_block(light_block, &validator_sets))
+                .collect::<Result<_, _>>()?,
+            commit_header_hashes: inputs.commit_header_hashes,
+            mode: u8::try_from(inputs.mode)
+                .ok()
+                .and_then(ProofMode::from_id)
+                .ok_or("unknown proof mode")?,
+        })
+    }
+}
+
+/// Encode the inputs as a `CompactProofInputs` protobuf message.
+pub fn encode(inputs: &ProofInputs) -> Vec<u8> {
+    CompactProofInputs::from(inputs).encode_to_vec()
+}
+
+/// Decode inputs from a `CompactProofInputs` protobuf message.
+pub fn decode(bytes: &[u8]) -> Result<ProofInputs, String> {
+    CompactProofInputs::decode(bytes)
+        .map_err(|err| err.to_string())?
+        .try_into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::tests::{assert_inputs_eq, test_proof_inputs};
+    use crate::types::ProofMode;
+
+    use super::*;
+
+    #[test]
+    fn test_compact_round_trip() {
+        for mode in [ProofMode::Skipping, ProofMode::Sequential] {
+            let mut inputs = test_proof_inputs();
+            inputs.mode = mode;
+            let decoded = decode(&encode(&inputs)).unwrap();
+            assert_inputs_eq(&decoded, &inputs);
+        }
+    }
+
+    #[test]
+    fn test_compact_shares_validator_sets() {
+        // Every light block of the test inputs has the same validator set.
+        let inputs = test_proof_inputs();
+        let message = CompactProofInputs::from(&inputs);
+        assert_eq!(message.validator_sets.len(), 1);
+
+        let mut other_validators = test_proof_inputs();
+        other_validators.target_light_block.next_validators =
+            ValidatorSet::without_proposer(Vec::new());
+        let message = CompactProofInputs::from(&other_validators);
+        assert_eq!(message.validator_sets.len(), 2);
+        assert_eq!(
+            message
+                .target_light_block
+                .as_ref()
+                .unwrap()
+                .next_validators_index,
+            1
+        );
+        assert_inputs_eq(
+            &decode(&message.encode_to_vec()).unwrap(),
+            &other_validators,
+        );
+    }
+
+    #[test]
+    fn test_compact_rejects_unknown_validator_set() {
+        let mut message = CompactProofInputs::from(&test_proof_inputs());
+        message
+            .trusted_light_block
+            .as_mut()
+            .unwrap()
+            .validators_index = 1;
+        assert!(decode(&message.encode_to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_compact_rejects_unknown_mode() {
+        let mut message = CompactProofInputs::from(&test_proof_inputs());
+        message.mode = 3;
+        assert!(decode(&message.encode_to_vec()).is_err());
+    }
+}
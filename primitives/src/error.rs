@@ -0,0 +1,137 @@
+use tendermint_light_client_verifier::{
+    errors::VerificationErrorDetail, operations::voting_power::VotingPowerTally,
+};
+use thiserror::Error;
+
+/// Errors returned when executing the Blobstream program logic.
+#[derive(Debug, Error)]
+pub enum BlobstreamError {
+    /// The trusted validators did not sign the block with enough voting power, i.e. the skip to
+    /// the block was too large.
+    #[error("Not enough trust to update to block {height}, voting power tally: {tally:?}")]
+    NotEnoughTrust {
+        height: u64,
+        tally: VotingPowerTally,
+    },
+    /// The light client verifier rejected the update to the block.
+    #[error("Could not verify updating to block {height}, error: {detail:?}")]
+    InvalidUpdate {
+        height: u64,
+        detail: VerificationErrorDetail,
+    },
+    /// The trust threshold in the verification parameters is not a valid fraction, or is below the
+    /// minimum of 2/3.
+    #[error("Invalid trust threshold {numerator}/{denominator}")]
+    InvalidTrustThreshold { numerator: u64, denominator: u64 },
+    /// The trusting period in the verification parameters is longer than the program allows.
+    #[error("Trusting period of {trusting_period_secs}s exceeds the maximum of {max_secs}s")]
+    TrustingPeriodTooLong {
+        trusting_period_secs: u64,
+        max_secs: u64,
+    },
+    /// The clock drift in the verification parameters is larger than the program allows.
+    #[error("Clock drift of {clock_drift_secs}s exceeds the maximum of {max_secs}s")]
+    ClockDriftTooLarge {
+        clock_drift_secs: u64,
+        max_secs: u64,
+    },
+    /// The time of the block's header is out of range.
+    #[error("Header time of block {height} is out of range")]
+    InvalidHeaderTime { height: u64 },
+    /// A header in the range is from a different chain than the trusted header.
+    #[error("Header of block {height} has chain ID {found}, expected {expected}")]
+    ChainIdMismatch {
+        height: u64,
+        expected: String,
+        found: String,
+    },
+    /// A pivot block is not the header at its height in the range.
+    #[error("Pivot block {height} is not in the header range")]
+    PivotNotInRange { height: u64 },
+    /// In sequential mode, intermediate headers were supplied besides the pivot blocks.
+    #[error(
+        "Sequential verification takes the headers from the pivot blocks, got {count} headers"
+    )]
+    UnexpectedSequentialHeaders { count: usize },
+    /// In next header mode, the target block is not the block directly after the trusted block, or
+    /// there are intermediate headers or pivot blocks.
+    #[error(
+        "Next header mode requires block {target_height} to directly follow block {trusted_height}"
+    )]
+    InvalidNextHeader {
+        trusted_height: u64,
+        target_height: u64,
+    },
+    /// The inputs are in a proof mode that the called function does not execute.
+    #[error("Proof mode {mode} is not supported here")]
+    UnexpectedProofMode { mode: u8 },
+    /// A header's height is not one more than the height of the previous header.
+    #[error(
+        "Header of block {height} follows block {previous_height}, expected consecutive heights"
+    )]
+    NonConsecutiveHeight { height: u64, previous_height: u64 },
+    /// A header's time is not after the time of the previous header.
+    #[error("Header time of block {height} is not after the previous header's time")]
+    NonIncreasingTime { height: u64 },
+    /// A header has no last block ID, so it cannot be linked to the previous header.
+    #[error("Header of block {height} has no last block ID")]
+    MissingLastBlockId { height: u64 },
+    /// A header's last block ID does not match the hash of the previous header.
+    #[error("Header of block {height} does not link to the previous header")]
+    HeaderLinkMismatch { height: u64 },
+    /// The validator set of a trusted block is not the set its header commits to.
+    #[error("Validator set of block {height} does not match the header's validators hash")]
+    ValidatorsHashMismatch { height: u64 },
+    /// The next validator set of a trusted block is not the set its header commits to.
+    #[error(
+        "Next validator set of block {height} does not match the header's next validators hash"
+    )]
+    NextValidatorsHashMismatch { height: u64 },
+    /// A header in the range has no data hash.
+    #[error("Header of block {height} has no data hash")]
+    MissingDataHash { height: u64 },
+    /// A header's app hash is not 32 bytes long.
+    #[error("Header of block {height} has an app hash that is not 32 bytes")]
+    InvalidAppHash { height: u64 },
+    /// The conflicting light blocks of misbehaviour evidence are at different heights.
+    #[error("Conflicting blocks are at different heights {height_a} and {height_b}")]
+    ConflictingHeightMismatch { height_a: u64, height_b: u64 },
+    /// The light blocks of misbehaviour evidence have the same header, so there is no fork.
+    #[error("Blocks at height {height} have the same header, there is no conflict")]
+    NoConflict { height: u64 },
+    /// No range proofs were supplied to aggregate.
+    #[error("No range proofs to aggregate")]
+    EmptyAggregation,
+    /// A range does not start at the target block of the previous range.
+    #[error("Range {index} does not start at the target block of the previous range")]
+    RangeNotContiguous { index: usize },
+    /// A range was proven for a different chain or with different verification params than the
+    /// previous range.
+    #[error(
+        "Range {index} has a different chain ID or verification params than the previous range"
+    )]
+    RangeParamsMismatch { index: usize },
+}
+
+impl BlobstreamError {
+    /// Whether the error means that the skip from the trusted block was too large, in which case a
+    /// closer target block should be requested. All other errors mean that the inputs are invalid,
+    /// e.g. because the Tendermint RPC returned bad data.
+    pub fn is_skip_too_large(&self) -> bool {
+        matches!(self, BlobstreamError::NotEnoughTrust { .. })
+    }
+}
+
+/// Errors returned when decoding encoded `ProofInputs`.
+#[derive(Debug, Error)]
+pub enum InputsDecodeError {
+    /// The encoding starts with the magic but is too short to contain the version.
+    #[error("Encoded proof inputs are truncated")]
+    Truncated,
+    /// The encoding has a version this build does not know how to decode.
+    #[error("Unsupported proof inputs version {version}")]
+    UnsupportedVersion { version: u16 },
+    /// The payload could not be deserialized as proof inputs of the given version.
+    #[error("Invalid proof inputs payload for version {version}: {message}")]
+    InvalidPayload { version: u16, message: String },
+}
@@ -0,0 +1,105 @@
+use alloy::primitives::B256;
+use alloy::sol;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tendermint_light_client_verifier::types::LightBlock;
+
+use crate::bitmap::{get_double_sign_bitmap, get_validator_bitmap_words, hash_validator_bitmap};
+use crate::error::BlobstreamError;
+use crate::types::VerificationParams;
+use crate::{check_trusted_validators, verify_header_update};
+
+sol! {
+    /// The public values committed by the evidence program, which proves a light client attack:
+    /// two different headers at the same height that are both valid updates from the same trusted
+    /// header.
+    ///
+    /// The double sign bitmap marks the validators of the trusted validator set that signed both
+    /// conflicting headers, in the same layout as the validator bitmap of `ProofOutputs`: the first
+    /// 256 validators are committed directly and the full bitmap through the validator count and
+    /// the bitmap hash.
+    struct EvidenceOutputs {
+        bytes32 trustedHeaderHash;
+        uint64 trustedBlock;
+        uint64 conflictingBlock;
+        bytes32 conflictingHeaderHashA;
+        bytes32 conflictingHeaderHashB;
+        uint256 doubleSignBitmap;
+        uint64 validatorCount;
+        bytes32 doubleSignBitmapHash;
+        bytes32 chainIdHash;
+        bytes32 verificationParamsHash;
+    }
+}
+
+/// The inputs of the evidence program.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EvidenceInputs {
+    pub trusted_light_block: LightBlock,
+    /// The two conflicting light blocks, which must be at the same height.
+    pub conflicting_light_block_a: LightBlock,
+    pub conflicting_light_block_b: LightBlock,
+    /// Light client options used to verify the updates to the conflicting blocks.
+    #[serde(default)]
+    pub verification_params: VerificationParams,
+}
+
+/// Check that the conflicting light blocks are a fork from the trusted block and return the outputs
+/// the evidence program commits for the inputs.
+pub fn execute_evidence(inputs: &EvidenceInputs) -> Result<EvidenceOutputs, BlobstreamError> {
+    let EvidenceInputs {
+        trusted_light_block,
+        conflicting_light_block_a,
+        conflicting_light_block_b,
+        verification_params,
+    } = inputs;
+
+    let height_a = conflicting_light_block_a.height().value();
+    let height_b = conflicting_light_block_b.height().value();
+    if height_a != height_b {
+        return Err(BlobstreamError::ConflictingHeightMismatch { height_a, height_b });
+    }
+
+    let header_hash_a = conflicting_light_block_a.signed_header.header.hash();
+    let header_hash_b = conflicting_light_block_b.signed_header.header.hash();
+    if header_hash_a == header_hash_b {
+        return Err(BlobstreamError::NoConflict { height: height_a });
+    }
+
+    // Both conflicting blocks must be valid updates from the trusted block, whose validator set
+    // orders the double sign bitmap.
+    check_trusted_validators(trusted_light_block)?;
+    verify_header_update(
+        trusted_light_block,
+        conflicting_light_block_a,
+        verification_params,
+    )?;
+    verify_header_update(
+        trusted_light_block,
+        conflicting_light_block_b,
+        verification_params,
+    )?;
+
+    let double_sign_bitmap = get_double_sign_bitmap(
+        trusted_light_block,
+        conflicting_light_block_a,
+        conflicting_light_block_b,
+    );
+
+    let trusted_header = &trusted_light_block.signed_header.header;
+    Ok(EvidenceOutputs {
+        trustedHeaderHash: B256::from_slice(trusted_header.hash().as_bytes()),
+        trustedBlock: trusted_header.height.value(),
+        conflictingBlock: height_a,
+        conflictingHeaderHashA: B256::from_slice(header_hash_a.as_bytes()),
+        conflictingHeaderHashB: B256::from_slice(header_hash_b.as_bytes()),
+        doubleSignBitmap: get_validator_bitmap_words(&double_sign_bitmap)
+            .first()
+            .copied()
+            .unwrap_or_default(),
+        validatorCount: double_sign_bitmap.len() as u64,
+        doubleSignBitmapHash: B256::from(hash_validator_bitmap(&double_sign_bitmap)),
+        chainIdHash: B256::from_slice(&Sha256::digest(trusted_header.chain_id.as_str().as_bytes())),
+        verificationParamsHash: B256::from(verification_params.hash()),
+    })
+}
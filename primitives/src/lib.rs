@@ -1,34 +1,357 @@
 use std::time::Duration;
 
+use alloy::primitives::B256;
+use alloy::sol_types::SolValue;
+use bitmap::{
+    get_commit_signers, get_validator_bitmap, get_validator_bitmap_words, hash_validator_bitmap,
+};
+use commitment::{validate_header_link, DataCommitmentScheme};
+use error::BlobstreamError;
+use sha2::{Digest, Sha256};
+use tendermint::Hash;
 use tendermint_light_client_verifier::{
-    options::Options,
-    types::{LightBlock, TrustThreshold},
-    ProdVerifier, Verdict, Verifier,
+    operations::voting_power::VotingPowerTally, types::LightBlock, ProdVerifier, Verdict, Verifier,
 };
+use types::{NextHeaderOutputs, ProofInputs, ProofMode, ProofOutputs, VerificationParams};
 
+pub mod aggregation;
+pub mod bitmap;
+pub mod commitment;
+pub mod compact;
+pub mod error;
+pub mod evidence;
 pub mod types;
 
-/// Get the verdict for the header update from trusted_block to target_block.
-pub fn get_header_update_verdict(trusted_block: &LightBlock, target_block: &LightBlock) -> Verdict {
-    let opt = Options {
-        // Note: For additional security, set the trust threshold to 2/3.
-        trust_threshold: TrustThreshold::TWO_THIRDS,
-        // 2 week trusting period is valid for chains with 21 day unbonding period.
-        trusting_period: Duration::from_secs(14 * 24 * 60 * 60),
-        clock_drift: Duration::ZERO,
-    };
+/// The scheme used for the data and header commitments. Defaults to the Blobstream scheme, which
+/// the SP1Blobstream contract verifies attestations against.
+#[cfg(not(feature = "keccak"))]
+pub type CommitmentScheme = commitment::BlobstreamScheme;
+#[cfg(feature = "keccak")]
+pub type CommitmentScheme = commitment::KeccakScheme;
+
+/// Get the verdict for the header update from trusted_block to target_block, using the supplied
+/// light client verification parameters.
+pub fn get_header_update_verdict(
+    trusted_block: &LightBlock,
+    target_block: &LightBlock,
+    params: &VerificationParams,
+) -> Result<Verdict, BlobstreamError> {
+    let opt = params.options()?;
 
     let vp = ProdVerifier::default();
 
     // Note: The zkVM has no notion of time, so no header will be rejected for being too
-    // far in the past, which is a potential issue. Deployers must ensure that the target block is not
-    // too far in the past, i.e. the light client must be relatively synced with the chain (i.e.
-    // within the trusting period).
-    let verify_time = target_block.time() + Duration::from_secs(10);
-    vp.verify_update_header(
+    // far in the past, which is a potential issue. The trusted and target header times are
+    // committed in the proof outputs along with the trusting period, so consumers can enforce
+    // that the trusted header was still within the trusting period when it was used.
+    let verify_time = (target_block.time() + Duration::from_secs(10)).map_err(|_| {
+        BlobstreamError::InvalidHeaderTime {
+            height: target_block.height().value(),
+        }
+    })?;
+    Ok(vp.verify_update_header(
         target_block.as_untrusted_state(),
         trusted_block.as_trusted_state(),
         &opt,
-        verify_time.unwrap(),
-    )
+        verify_time,
+    ))
+}
+
+/// Verify the header update from trusted_block to target_block, returning an error if the verdict
+/// is not Success.
+pub fn verify_header_update(
+    trusted_block: &LightBlock,
+    target_block: &LightBlock,
+    params: &VerificationParams,
+) -> Result<(), BlobstreamError> {
+    let height = target_block.height().value();
+    match get_header_update_verdict(trusted_block, target_block, params)? {
+        Verdict::Success => Ok(()),
+        Verdict::NotEnoughTrust(tally) => Err(BlobstreamError::NotEnoughTrust { height, tally }),
+        Verdict::Invalid(detail) => Err(BlobstreamError::InvalidUpdate { height, detail }),
+    }
+}
+
+/// Check that the validator sets of the trusted block are the sets its header commits to. The
+/// verifier only checks the validator sets of the untrusted block, but the validator bitmap is
+/// ordered by the trusted validator set, which must be identified by the committed
+/// `trustedValidatorsHash`, and the voting power tally is over the trusted next validator set.
+pub fn check_trusted_validators(trusted_block: &LightBlock) -> Result<(), BlobstreamError> {
+    let header = &trusted_block.signed_header.header;
+    if trusted_block.validators.hash() != header.validators_hash {
+        return Err(BlobstreamError::ValidatorsHashMismatch {
+            height: header.height.value(),
+        });
+    }
+    if trusted_block.next_validators.hash() != header.next_validators_hash {
+        return Err(BlobstreamError::NextValidatorsHashMismatch {
+            height: header.height.value(),
+        });
+    }
+    Ok(())
+}
+
+/// Tally the voting power of the trusted block's next validator set that signed the target block's
+/// commit, as the verifier does for the update. Only call this once the update is verified: the
+/// verifier has then checked the signature of every validator of the set in the commit, so the
+/// tally sums their power without verifying the signatures again.
+pub fn get_voting_power_tally(
+    trusted_block: &LightBlock,
+    target_block: &LightBlock,
+    params: &VerificationParams,
+) -> Result<VotingPowerTally, BlobstreamError> {
+    let opt = params.options()?;
+    let signers = get_commit_signers(target_block);
+    let tallied = trusted_block
+        .next_validators
+        .validators()
+        .iter()
+        .filter(|validator| signers.contains(&validator.address))
+        .map(|validator| validator.power())
+        .sum();
+    Ok(VotingPowerTally {
+        total: trusted_block.next_validators.total_voting_power().value(),
+        tallied,
+        trust_threshold: opt.trust_threshold,
+    })
+}
+
+/// Execute the Blobstream program logic with the default commitment scheme and return the outputs
+/// the program commits for the inputs. The program calls this too, so the host can compute the
+/// expected outputs without the zkVM.
+pub fn execute(inputs: &ProofInputs) -> Result<ProofOutputs, BlobstreamError> {
+    execute_with_scheme::<CommitmentScheme>(inputs)
+}
+
+/// Execute the Blobstream program logic and ABI encode the public values the program commits. In
+/// next header mode these are the `NextHeaderOutputs`. Otherwise, with the `app-hash` feature these
+/// are the `ExtendedProofOutputs`, and without it the `ProofOutputs`.
+pub fn execute_public_values(inputs: &ProofInputs) -> Result<Vec<u8>, BlobstreamError> {
+    if inputs.mode == ProofMode::NextHeader {
+        return Ok(execute_next_header_with_scheme::<CommitmentScheme>(inputs)?.abi_encode());
+    }
+
+    let outputs = execute(inputs)?;
+
+    #[cfg(feature = "app-hash")]
+    {
+        let target_header = &inputs.target_light_block.signed_header.header;
+        let app_hash = target_header.app_hash.as_bytes();
+        if app_hash.len() != 32 {
+            return Err(BlobstreamError::InvalidAppHash {
+                height: target_header.height.value(),
+            });
+        }
+        Ok(types::ExtendedProofOutputs {
+            outputs,
+            targetAppHash: B256::from_slice(app_hash),
+        }
+        .abi_encode())
+    }
+
+    #[cfg(not(feature = "app-hash"))]
+    Ok(outputs.abi_encode())
+}
+
+/// Execute the next header program logic with the given commitment scheme. The range is the single
+/// step from the trusted block to the next block, so the data commitment is the hash of a single
+/// leaf and there is nothing to skip over.
+pub fn execute_next_header_with_scheme<S: DataCommitmentScheme>(
+    inputs: &ProofInputs,
+) -> Result<NextHeaderOutputs, BlobstreamError> {
+    let ProofInputs {
+        trusted_light_block,
+        target_light_block,
+        headers,
+        verification_params,
+        pivot_light_blocks,
+        commit_header_hashes: _,
+        mode,
+    } = inputs;
+
+    if *mode != ProofMode::NextHeader {
+        return Err(BlobstreamError::UnexpectedProofMode { mode: mode.id() });
+    }
+    let trusted_height = trusted_light_block.height().value();
+    let target_height = target_light_block.height().value();
+    if trusted_height.checked_add(1) != Some(target_height)
+        || !headers.is_empty()
+        || !pivot_light_blocks.is_empty()
+    {
+        return Err(BlobstreamError::InvalidNextHeader {
+            trusted_height,
+            target_height,
+        });
+    }
+
+    check_trusted_validators(trusted_light_block)?;
+
+    let trusted_header = &trusted_light_block.signed_header.header;
+    let target_header = &target_light_block.signed_header.header;
+    let trusted_header_hash = trusted_header.hash();
+    validate_header_link(trusted_header, &trusted_header_hash, target_header)?;
+    verify_header_update(trusted_light_block, target_light_block, verification_params)?;
+
+    let data_root = |header: &tendermint::block::Header| {
+        header
+            .data_hash
+            .and_then(|data_hash| <[u8; 32]>::try_from(data_hash.as_bytes()).ok())
+            .ok_or(BlobstreamError::MissingDataHash {
+                height: header.height.value(),
+            })
+    };
+    let data_commitment = S::leaf_hash(&S::encode_leaf(trusted_height, data_root(trusted_header)?));
+
+    let chain_id = trusted_header.chain_id.as_str();
+    Ok(NextHeaderOutputs {
+        trustedHeaderHash: B256::from_slice(trusted_header_hash.as_bytes()),
+        targetHeaderHash: B256::from_slice(target_header.hash().as_bytes()),
+        dataCommitment: B256::from(data_commitment),
+        trustedBlock: trusted_height,
+        targetBlock: target_height,
+        validatorBitmap: Default::default(),
+        targetDataRoot: B256::from(data_root(target_header)?),
+        trustedHeaderTime: trusted_header.time.unix_timestamp() as u64,
+        targetHeaderTime: target_header.time.unix_timestamp() as u64,
+        trustingPeriod: verification_params.trusting_period_secs,
+        chainIdHash: B256::from_slice(&Sha256::digest(chain_id.as_bytes())),
+        verificationParamsHash: B256::from(verification_params.hash()),
+        proofMode: mode.id(),
+    })
+}
+
+/// Execute the Blobstream program logic with the given commitment scheme. Next header inputs are
+/// executed by `execute_next_header_with_scheme` instead.
+pub fn execute_with_scheme<S: DataCommitmentScheme>(
+    inputs: &ProofInputs,
+) -> Result<ProofOutputs, BlobstreamError> {
+    let ProofInputs {
+        trusted_light_block,
+        target_light_block,
+        headers,
+        verification_params,
+        pivot_light_blocks,
+        commit_header_hashes,
+        mode,
+    } = inputs;
+
+    if *mode == ProofMode::NextHeader {
+        return Err(BlobstreamError::UnexpectedProofMode { mode: mode.id() });
+    }
+    let trusted_height = trusted_light_block.height().value();
+    let target_height = target_light_block.height().value();
+
+    check_trusted_validators(trusted_light_block)?;
+
+    // In sequential mode, there is a pivot at every height in the range, so the intermediate headers
+    // are taken from the pivot blocks instead of being decoded twice. The data commitment checks
+    // that they are consecutive, so every hop is between adjacent blocks and each block's commit is
+    // verified against its own validator set.
+    if *mode == ProofMode::Sequential && !headers.is_empty() {
+        return Err(BlobstreamError::UnexpectedSequentialHeaders {
+            count: headers.len(),
+        });
+    }
+
+    let trusted_header = &trusted_light_block.signed_header.header;
+    let target_header = &target_light_block.signed_header.header;
+    let mut all_headers = Vec::with_capacity(headers.len() + pivot_light_blocks.len() + 2);
+    all_headers.push(trusted_header);
+    if *mode == ProofMode::Sequential {
+        all_headers.extend(
+            pivot_light_blocks
+                .iter()
+                .map(|pivot| &pivot.signed_header.header),
+        );
+    } else {
+        all_headers.extend(headers.iter());
+    }
+    all_headers.push(target_header);
+
+    // Hash each header exactly once, as hashing headers dominates the cost of long ranges.
+    let header_hashes: Vec<Hash> = all_headers.iter().map(|header| header.hash()).collect();
+
+    // Compute the data commitment across the range. This also validates that the headers form a
+    // chain of consecutive blocks from the trusted block to the target block, so every header in
+    // the range is on the trusted header's chain.
+    let data_commitment = B256::from(S::data_commitment(&all_headers, &header_hashes)?);
+    let range_length = (all_headers.len() - 1) as u64;
+
+    let chain_id = &trusted_header.chain_id;
+    let chain_id_hash = B256::from_slice(&Sha256::digest(chain_id.as_str().as_bytes()));
+
+    // Each pivot must be the header at its height in the range, so that the verified hops and the
+    // data commitment are over the same chain of headers. Comparing the headers directly avoids
+    // hashing the pivot headers. In sequential mode, the range is built from the pivots.
+    if *mode != ProofMode::Sequential {
+        for pivot in pivot_light_blocks {
+            let index = pivot.height().value().saturating_sub(trusted_height) as usize;
+            if index == 0
+                || index >= all_headers.len() - 1
+                || *all_headers[index] != pivot.signed_header.header
+            {
+                return Err(BlobstreamError::PivotNotInRange {
+                    height: pivot.height().value(),
+                });
+            }
+        }
+    }
+
+    // Verify the update from the trusted block to the target block, hopping through each of the
+    // pivot blocks in order.
+    let mut hop_trusted_block = trusted_light_block;
+    for hop_target_block in pivot_light_blocks {
+        verify_header_update(hop_trusted_block, hop_target_block, verification_params)?;
+        hop_trusted_block = hop_target_block;
+    }
+    verify_header_update(hop_trusted_block, target_light_block, verification_params)?;
+
+    // Tally the voting power that carried the last hop to the target block, so consumers can see
+    // by how much the update crossed the trust threshold.
+    let tally = get_voting_power_tally(hop_trusted_block, target_light_block, verification_params)?;
+
+    // Optionally compute the commitment to the header hashes across the range.
+    let header_commitment = if *commit_header_hashes {
+        B256::from(S::header_commitment(&header_hashes))
+    } else {
+        B256::ZERO
+    };
+
+    // Get the validator bitmap. The full bitmap is committed by its hash, and the bitmap of the first
+    // 256 validators is also committed directly.
+    let validator_bitmap = get_validator_bitmap(trusted_light_block, target_light_block);
+    let validator_bitmap_u256 = get_validator_bitmap_words(&validator_bitmap)
+        .first()
+        .copied()
+        .unwrap_or_default();
+
+    let target_data_root = target_header
+        .data_hash
+        .and_then(|data_hash| B256::try_from(data_hash.as_bytes()).ok())
+        .ok_or(BlobstreamError::MissingDataHash {
+            height: target_height,
+        })?;
+    Ok(ProofOutputs {
+        trustedHeaderHash: B256::from_slice(header_hashes[0].as_bytes()),
+        targetHeaderHash: B256::from_slice(header_hashes[header_hashes.len() - 1].as_bytes()),
+        dataCommitment: data_commitment,
+        trustedBlock: trusted_header.height.value(),
+        targetBlock: target_header.height.value(),
+        validatorBitmap: validator_bitmap_u256,
+        trustedHeaderTime: trusted_header.time.unix_timestamp() as u64,
+        targetHeaderTime: target_header.time.unix_timestamp() as u64,
+        trustingPeriod: verification_params.trusting_period_secs,
+        chainIdHash: chain_id_hash,
+        verificationParamsHash: B256::from(verification_params.hash()),
+        validatorCount: validator_bitmap.len() as u64,
+        validatorBitmapHash: B256::from(hash_validator_bitmap(&validator_bitmap)),
+        trustedValidatorsHash: B256::from_slice(trusted_header.validators_hash.as_bytes()),
+        targetNextValidatorsHash: B256::from_slice(target_header.next_validators_hash.as_bytes()),
+        headerCommitment: header_commitment,
+        rangeLength: range_length,
+        proofMode: mode.id(),
+        totalVotingPower: tally.total,
+        signedVotingPower: tally.tallied,
+        targetDataRoot: target_data_root,
+    })
 }
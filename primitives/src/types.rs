@@ -1,22 +1,543 @@
+use std::time::Duration;
+
 use alloy::sol;
+use alloy::sol_types::SolValue;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tendermint::block::Header;
-use tendermint_light_client_verifier::types::LightBlock;
-
-/// bytes32 trusted_header_hash;
-/// bytes32 target_header_hash;
-/// bytes32 data_commitment;
-/// uint64 trusted_block;
-/// uint64 target_block;
-/// uint256 validator_bitmap;
-pub type ProofOutputs = sol! {
-    tuple(bytes32, bytes32, bytes32, uint64, uint64, uint256)
+
+use crate::compact;
+use crate::error::{BlobstreamError, InputsDecodeError};
+use tendermint_light_client_verifier::{
+    options::Options,
+    types::{LightBlock, TrustThreshold},
 };
 
+sol! {
+    /// The public values committed by the Blobstream program. New fields are only ever appended,
+    /// so the layout stays decodable by contracts that only know about a prefix of the fields.
+    ///
+    /// Header times are Unix timestamps in seconds and the trusting period is in seconds. As the
+    /// zkVM has no clock, consumers can use these to check that the trusted header was still within
+    /// the trusting period when the target header was verified.
+    ///
+    /// The chain ID hash is the SHA-256 hash of the Tendermint chain ID shared by every header in
+    /// the range, which binds the proof to a single chain.
+    ///
+    /// The verification params hash commits to the `VerificationParams` the target header was
+    /// verified with.
+    ///
+    /// The validator bitmap only covers the first 256 validators of the trusted validator set. For
+    /// larger sets, the full bitmap is committed through the validator count and the bitmap hash,
+    /// see `bitmap::hash_validator_bitmap`.
+    ///
+    /// The trusted validators hash is the `validators_hash` of the trusted header, which identifies
+    /// the validator set the bitmap is ordered by. The program checks that the trusted validator
+    /// set hashes to it, so the validator count and bitmap hash are over that set. The target next
+    /// validators hash is the `next_validators_hash` of the target header, the validator set of the
+    /// next block.
+    ///
+    /// The header commitment is the root of a Tendermint Merkle tree over the header hashes of the
+    /// blocks in [trustedBlock, targetBlock), which lets consumers open any field of an intermediate
+    /// header. It is zero unless `ProofInputs::commit_header_hashes` is set.
+    ///
+    /// The range length is the number of headers in [trustedBlock, targetBlock) that were checked
+    /// to link to their successor by height, time, chain ID and last block ID, i.e.
+    /// `targetBlock - trustedBlock`.
+    ///
+    /// The proof mode is the `ProofMode` the update was verified in: 0 for skipping verification,
+    /// 1 for sequential verification of every block. Next header proofs commit the
+    /// `NextHeaderOutputs` instead.
+    ///
+    /// The total voting power is the voting power of the validator set trusted for the last hop to
+    /// the target block (the next validators of the trusted block or of the last pivot block), and
+    /// the signed voting power is how much of it signed the target block's commit. The update is
+    /// only valid if `signedVotingPower / totalVotingPower` exceeds the trust threshold.
+    ///
+    /// The target data root is the data hash of the target header. It is not part of the data
+    /// commitment, which ends before the target block, so it is committed directly.
+    struct ProofOutputs {
+        bytes32 trustedHeaderHash;
+        bytes32 targetHeaderHash;
+        bytes32 dataCommitment;
+        uint64 trustedBlock;
+        uint64 targetBlock;
+        uint256 validatorBitmap;
+        uint64 trustedHeaderTime;
+        uint64 targetHeaderTime;
+        uint64 trustingPeriod;
+        bytes32 chainIdHash;
+        bytes32 verificationParamsHash;
+        uint64 validatorCount;
+        bytes32 validatorBitmapHash;
+        bytes32 trustedValidatorsHash;
+        bytes32 targetNextValidatorsHash;
+        bytes32 headerCommitment;
+        uint64 rangeLength;
+        uint8 proofMode;
+        uint64 totalVotingPower;
+        uint64 signedVotingPower;
+        bytes32 targetDataRoot;
+    }
+
+    /// The public values committed by the program in next header mode, which verifies a single
+    /// step from the trusted block to the next block without building the Merkle tree, the
+    /// validator bitmap or the voting power tally.
+    ///
+    /// The fields up to the validator bitmap have the same layout as `ProofOutputs`, so the
+    /// contract's `commitHeaderRange` accepts next header proofs. The data commitment of the single
+    /// block range [trustedBlock, targetBlock) is the hash of the leaf of the trusted block, which
+    /// is the root of a tree with that one leaf, and the validator bitmap is always zero.
+    ///
+    /// The target data root is the data hash of the new header. The other fields are as in
+    /// `ProofOutputs`, and the proof mode is always 2. The outputs are the same with the `app-hash`
+    /// feature, and the aggregation program does not accept them, as they do not decode as
+    /// `ProofOutputs`.
+    struct NextHeaderOutputs {
+        bytes32 trustedHeaderHash;
+        bytes32 targetHeaderHash;
+        bytes32 dataCommitment;
+        uint64 trustedBlock;
+        uint64 targetBlock;
+        uint256 validatorBitmap;
+        bytes32 targetDataRoot;
+        uint64 trustedHeaderTime;
+        uint64 targetHeaderTime;
+        uint64 trustingPeriod;
+        bytes32 chainIdHash;
+        bytes32 verificationParamsHash;
+        uint8 proofMode;
+    }
+
+    /// The public values committed by the program when built with the `app-hash` feature. The
+    /// outputs are encoded in place, so the encoding is the encoding of `ProofOutputs` followed by
+    /// the extra fields and stays decodable as `ProofOutputs`.
+    ///
+    /// The target app hash is the `app_hash` of the target header, the root of the Celestia
+    /// application state after the block before the target block, which consumers can verify
+    /// state proofs against. The next validators hash of the target header is already committed
+    /// in the outputs.
+    struct ExtendedProofOutputs {
+        ProofOutputs outputs;
+        bytes32 targetAppHash;
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProofInputs {
     pub trusted_light_block: LightBlock,
     pub target_light_block: LightBlock,
-    /// Exclusive of trusted_light_block and target_light_block's headers
+    /// Exclusive of trusted_light_block and target_light_block's headers. Empty in sequential mode,
+    /// where the headers are those of the pivot blocks.
     pub headers: Vec<Header>,
+    /// Light client options used to verify the update from the trusted to the target block.
+    #[serde(default)]
+    pub verification_params: VerificationParams,
+    /// Intermediate light blocks, ordered by height, to verify the update through. Each hop from
+    /// the trusted block through the pivots to the target block must be a valid update on its own.
+    /// Except in sequential mode, their headers must also be included in `headers`.
+    #[serde(default)]
+    pub pivot_light_blocks: Vec<LightBlock>,
+    /// Whether to commit to the header hashes of the range in addition to the data roots.
+    #[serde(default)]
+    pub commit_header_hashes: bool,
+    /// How the update from the trusted to the target block is verified.
+    #[serde(default)]
+    pub mode: ProofMode,
+}
+
+/// How the update from the trusted to the target block is verified.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofMode {
+    /// Skip from the trusted block through the pivot blocks to the target block, trusting that the
+    /// validators who signed each block overlap with the validators of the previous hop.
+    #[default]
+    Skipping,
+    /// Verify every block's commit against its own validator set. `pivot_light_blocks` must hold
+    /// the light block of every height strictly between the trusted and target blocks, so each
+    /// hop is between adjacent blocks, and `headers` must be empty.
+    Sequential,
+    /// Step from the trusted block to the block directly after it, with no intermediate headers or
+    /// pivot blocks. This is the cheapest mode, for low-latency single block updates, and commits
+    /// the smaller `NextHeaderOutputs` instead of the `ProofOutputs`.
+    NextHeader,
+}
+
+impl ProofMode {
+    /// The identifier of the mode committed in `ProofOutputs::proofMode` and
+    /// `NextHeaderOutputs::proofMode`.
+    pub fn id(&self) -> u8 {
+        match self {
+            ProofMode::Skipping => 0,
+            ProofMode::Sequential => 1,
+            ProofMode::NextHeader => 2,
+        }
+    }
+
+    /// The mode with the given identifier.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(ProofMode::Skipping),
+            1 => Some(ProofMode::Sequential),
+            2 => Some(ProofMode::NextHeader),
+            _ => None,
+        }
+    }
+}
+
+/// The magic prefix of versioned `ProofInputs` encodings. Legacy encodings are a bare CBOR map,
+/// which can never start with these bytes.
+pub const PROOF_INPUTS_MAGIC: [u8; 4] = *b"BSPI";
+
+/// The encoding of the `ProofInputs` payload, identified by the envelope version.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputsEncoding {
+    /// Version 1: the CBOR encoding of `ProofInputs`.
+    #[default]
+    Cbor,
+    /// Version 2: the compact protobuf encoding of `ProofInputs`, see `compact`. This is cheaper
+    /// to decode in the zkVM.
+    Compact,
+}
+
+impl InputsEncoding {
+    /// The envelope version of the encoding.
+    pub fn version(&self) -> u16 {
+        match self {
+            InputsEncoding::Cbor => 1,
+            InputsEncoding::Compact => 2,
+        }
+    }
+}
+
+impl ProofInputs {
+    /// Encode the inputs in the versioned envelope with the default encoding.
+    pub fn encode(&self) -> Vec<u8> {
+        self.encode_as(InputsEncoding::default())
+    }
+
+    /// Encode the inputs in the versioned envelope: the magic, the big-endian version of the
+    /// encoding and the payload.
+    pub fn encode_as(&self, encoding: InputsEncoding) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        encoded.extend_from_slice(&PROOF_INPUTS_MAGIC);
+        encoded.extend_from_slice(&encoding.version().to_be_bytes());
+        match encoding {
+            InputsEncoding::Cbor => {
+                serde_cbor::to_writer(&mut encoded, self).expect("failed to encode proof inputs")
+            }
+            InputsEncoding::Compact => encoded.extend(compact::encode(self)),
+        }
+        encoded
+    }
+
+    /// Decode inputs written by `encode_as`. Inputs without the magic prefix are decoded as the
+    /// legacy unversioned CBOR encoding.
+    pub fn decode(bytes: &[u8]) -> Result<Self, InputsDecodeError> {
+        let Some(rest) = bytes.strip_prefix(PROOF_INPUTS_MAGIC.as_slice()) else {
+            return Self::decode_cbor(bytes, 0);
+        };
+        if rest.len() < 2 {
+            return Err(InputsDecodeError::Truncated);
+        }
+        let (version, payload) = rest.split_at(2);
+        let version = u16::from_be_bytes([version[0], version[1]]);
+        match version {
+            1 => Self::decode_cbor(payload, version),
+            2 => compact::decode(payload)
+                .map_err(|message| InputsDecodeError::InvalidPayload { version, message }),
+            _ => Err(InputsDecodeError::UnsupportedVersion { version }),
+        }
+    }
+
+    /// Decode a CBOR payload, reporting errors against the envelope version (0 for legacy inputs).
+    fn decode_cbor(payload: &[u8], version: u16) -> Result<Self, InputsDecodeError> {
+        serde_cbor::from_slice(payload).map_err(|err| InputsDecodeError::InvalidPayload {
+            version,
+            message: err.to_string(),
+        })
+    }
+}
+
+/// The longest trusting period the program accepts, the 2 week trusting period of Celestia. Chains
+/// with shorter unbonding periods can use a shorter trusting period.
+pub const MAX_TRUSTING_PERIOD_SECS: u64 = 14 * 24 * 60 * 60;
+
+/// The largest clock drift the program accepts.
+pub const MAX_CLOCK_DRIFT_SECS: u64 = 10;
+
+/// Light client verification options. These are supplied with the inputs rather than hard-coded in
+/// the program, so a single program can serve chains with different unbonding periods.
+///
+/// The contract does not check the committed `verificationParamsHash`, so the program bounds the
+/// parameters instead: the trust threshold must be at least 2/3, the trusting period at most
+/// `MAX_TRUSTING_PERIOD_SECS` and the clock drift at most `MAX_CLOCK_DRIFT_SECS`. A prover can only
+/// make verification stricter than the Celestia defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationParams {
+    /// Numerator of the fraction of the trusted validator set's voting power that must have signed
+    /// the target header.
+    pub trust_threshold_numerator: u64,
+    /// Denominator of the trust threshold.
+    pub trust_threshold_denominator: u64,
+    /// Trusting period in seconds. Should be less than the chain's unbonding period.
+    pub trusting_period_secs: u64,
+    /// Tolerated clock drift in seconds.
+    pub clock_drift_secs: u64,
+}
+
+impl Default for VerificationParams {
+    /// The parameters used for Celestia. Note: For additional security, the trust threshold is 2/3.
+    /// A 2 week trusting period is valid for chains with a 21 day unbonding period.
+    fn default() -> Self {
+        Self {
+            trust_threshold_numerator: 2,
+            trust_threshold_denominator: 3,
+            trusting_period_secs: MAX_TRUSTING_PERIOD_SECS,
+            clock_drift_secs: 0,
+        }
+    }
+}
+
+impl VerificationParams {
+    /// Convert the parameters into the light client verifier's options, checking that they are
+    /// within the bounds the program accepts.
+    pub fn options(&self) -> Result<Options, BlobstreamError> {
+        let invalid_trust_threshold = || BlobstreamError::InvalidTrustThreshold {
+            numerator: self.trust_threshold_numerator,
+            denominator: self.trust_threshold_denominator,
+        };
+        let trust_threshold = TrustThreshold::new(
+            self.trust_threshold_numerator,
+            self.trust_threshold_denominator,
+        )
+        .map_err(|_| invalid_trust_threshold())?;
+        if (self.trust_threshold_numerator as u128) * 3
+            < (self.trust_threshold_denominator as u128) * 2
+        {
+            return Err(invalid_trust_threshold());
+        }
+        if self.trusting_period_secs > MAX_TRUSTING_PERIOD_SECS {
+            return Err(BlobstreamError::TrustingPeriodTooLong {
+                trusting_period_secs: self.trusting_period_secs,
+                max_secs: MAX_TRUSTING_PERIOD_SECS,
+            });
+        }
+        if self.clock_drift_secs > MAX_CLOCK_DRIFT_SECS {
+            return Err(BlobstreamError::ClockDriftTooLarge {
+                clock_drift_secs: self.clock_drift_secs,
+                max_secs: MAX_CLOCK_DRIFT_SECS,
+            });
+        }
+        Ok(Options {
+            trust_threshold,
+            trusting_period: Duration::from_secs(self.trusting_period_secs),
+            clock_drift: Duration::from_secs(self.clock_drift_secs),
+        })
+    }
+
+    /// The SHA-256 hash of the ABI-encoded parameters, in field order.
+    pub fn hash(&self) -> [u8; 32] {
+        let encoded = (
+            self.trust_threshold_numerator,
+            self.trust_threshold_denominator,
+            self.trusting_period_secs,
+            self.clock_drift_secs,
+        )
+            .abi_encode();
+        Sha256::digest(encoded).into()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use tendermint::{
+        block::{self, header::Version, signed_header::SignedHeader, CommitSig, Height},
+        chain, node, validator, AppHash, Hash, PublicKey, Time,
+    };
+
+    use super::*;
+
+    /// A light block at the height. The commit is not signed, so it only passes decoding.
+    pub(crate) fn test_light_block(height: u64) -> LightBlock {
+        let pub_key = PublicKey::from_raw_ed25519(&[1; 32]).unwrap();
+        let validator = validator::Info::new(pub_key, 10u32.into());
+        let validators = validator::Set::without_proposer(vec![validator]);
+        let header = Header {
+            version: Version { block: 11, app: 1 },
+            chain_id: "test-chain".parse::<chain::Id>().unwrap(),
+            height: Height::try_from(height).unwrap(),
+            time: Time::from_unix_timestamp(1_700_000_000 + height as i64, 0).unwrap(),
+            last_block_id: Some(block::Id {
+                hash: Hash::Sha256([height as u8; 32]),
+                part_set_header: block::parts::Header::new(1, Hash::Sha256([5; 32])).unwrap(),
+            }),
+            last_commit_hash: None,
+            data_hash: Some(Hash::Sha256([2; 32])),
+            validators_hash: validators.hash(),
+            next_validators_hash: validators.hash(),
+            consensus_hash: Hash::Sha256([3; 32]),
+            app_hash: AppHash::try_from(vec![4; 32]).unwrap(),
+            last_results_hash: None,
+            evidence_hash: None,
+            proposer_address: validator.address,
+        };
+        let commit = block::Commit {
+            height: header.height,
+            round: block::Round::default(),
+            block_id: block::Id {
+                hash: header.hash(),
+                part_set_header: block::parts::Header::new(1, Hash::Sha256([6; 32])).unwrap(),
+            },
+            signatures: vec![CommitSig::BlockIdFlagAbsent],
+        };
+        LightBlock::new(
+            SignedHeader::new(header, commit).unwrap(),
+            validators.clone(),
+            validators,
+            node::Id::new([0; 20]),
+        )
+    }
+
+    /// Inputs for the range from block 2 to block 5 through a pivot at block 3.
+    pub(crate) fn test_proof_inputs() -> ProofInputs {
+        let pivot_light_block = test_light_block(3);
+        ProofInputs {
+            trusted_light_block: test_light_block(2),
+            target_light_block: test_light_block(5),
+            headers: vec![
+                pivot_light_block.signed_header.header.clone(),
+                test_light_block(4).signed_header.header,
+            ],
+            verification_params: VerificationParams {
+                trusting_period_secs: 7 * 24 * 60 * 60,
+                ..VerificationParams::default()
+            },
+            pivot_light_blocks: vec![pivot_light_block],
+            commit_header_hashes: true,
+            mode: ProofMode::Skipping,
+        }
+    }
+
+    pub(crate) fn assert_inputs_eq(actual: &ProofInputs, expected: &ProofInputs) {
+        assert_eq!(actual.trusted_light_block, expected.trusted_light_block);
+        assert_eq!(actual.target_light_block, expected.target_light_block);
+        assert_eq!(actual.headers, expected.headers);
+        assert_eq!(actual.verification_params, expected.verification_params);
+        assert_eq!(actual.pivot_light_blocks, expected.pivot_light_blocks);
+        assert_eq!(actual.commit_header_hashes, expected.commit_header_hashes);
+        assert_eq!(actual.mode, expected.mode);
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let inputs = test_proof_inputs();
+        for encoding in [InputsEncoding::Cbor, InputsEncoding::Compact] {
+            let decoded = ProofInputs::decode(&inputs.encode_as(encoding)).unwrap();
+            assert_inputs_eq(&decoded, &inputs);
+        }
+    }
+
+    #[test]
+    fn test_decode_legacy_cbor() {
+        /// The unversioned inputs written before the envelope was introduced.
+        #[derive(Serialize)]
+        struct LegacyProofInputs<'a> {
+            trusted_light_block: &'a LightBlock,
+            target_light_block: &'a LightBlock,
+            headers: &'a [Header],
+        }
+
+        let inputs = test_proof_inputs();
+        let legacy = serde_cbor::to_vec(&LegacyProofInputs {
+            trusted_light_block: &inputs.trusted_light_block,
+            target_light_block: &inputs.target_light_block,
+            headers: &inputs.headers,
+        })
+        .unwrap();
+
+        let decoded = ProofInputs::decode(&legacy).unwrap();
+        assert_inputs_eq(
+            &decoded,
+            &ProofInputs {
+                trusted_light_block: inputs.trusted_light_block.clone(),
+                target_light_block: inputs.target_light_block.clone(),
+                headers: inputs.headers.clone(),
+                verification_params: VerificationParams::default(),
+                pivot_light_blocks: Vec::new(),
+                commit_header_hashes: false,
+                mode: ProofMode::Skipping,
+            },
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(matches!(
+            ProofInputs::decode(&PROOF_INPUTS_MAGIC),
+            Err(InputsDecodeError::Truncated)
+        ));
+
+        let mut unsupported = PROOF_INPUTS_MAGIC.to_vec();
+        unsupported.extend_from_slice(&3u16.to_be_bytes());
+        assert!(matches!(
+            ProofInputs::decode(&unsupported),
+            Err(InputsDecodeError::UnsupportedVersion { version: 3 })
+        ));
+
+        let mut invalid = test_proof_inputs().encode_as(InputsEncoding::Compact);
+        invalid.truncate(invalid.len() / 2);
+        assert!(matches!(
+            ProofInputs::decode(&invalid),
+            Err(InputsDecodeError::InvalidPayload { version: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_verification_params_bounds() {
+        let params =
+            |numerator, denominator, trusting_period_secs, clock_drift_secs| VerificationParams {
+                trust_threshold_numerator: numerator,
+                trust_threshold_denominator: denominator,
+                trusting_period_secs,
+                clock_drift_secs,
+            };
+
+        // The trust threshold must be at least 2/3.
+        assert!(params(2, 3, MAX_TRUSTING_PERIOD_SECS, 0).options().is_ok());
+        assert!(params(3, 4, MAX_TRUSTING_PERIOD_SECS, 0).options().is_ok());
+        assert!(params(1, 1, MAX_TRUSTING_PERIOD_SECS, 0).options().is_ok());
+        for (numerator, denominator) in [(3, 5), (1, 3), (666, 1000), (2, 0), (4, 3)] {
+            assert!(matches!(
+                params(numerator, denominator, MAX_TRUSTING_PERIOD_SECS, 0).options(),
+                Err(BlobstreamError::InvalidTrustThreshold { .. })
+            ));
+        }
+
+        // The trusting period must be at most MAX_TRUSTING_PERIOD_SECS.
+        assert!(params(2, 3, MAX_TRUSTING_PERIOD_SECS - 1, 0)
+            .options()
+            .is_ok());
+        assert!(matches!(
+            params(2, 3, MAX_TRUSTING_PERIOD_SECS + 1, 0).options(),
+            Err(BlobstreamError::TrustingPeriodTooLong {
+                trusting_period_secs,
+                max_secs: MAX_TRUSTING_PERIOD_SECS,
+            }) if trusting_period_secs == MAX_TRUSTING_PERIOD_SECS + 1
+        ));
+
+        // The clock drift must be at most MAX_CLOCK_DRIFT_SECS.
+        assert!(params(2, 3, MAX_TRUSTING_PERIOD_SECS, MAX_CLOCK_DRIFT_SECS)
+            .options()
+            .is_ok());
+        assert!(matches!(
+            params(2, 3, MAX_TRUSTING_PERIOD_SECS, MAX_CLOCK_DRIFT_SECS + 1).options(),
+            Err(BlobstreamError::ClockDriftTooLarge {
+                clock_drift_secs,
+                max_secs: MAX_CLOCK_DRIFT_SECS,
+            }) if clock_drift_secs == MAX_CLOCK_DRIFT_SECS + 1
+        ));
+
+        assert!(VerificationParams::default().options().is_ok());
+    }
 }
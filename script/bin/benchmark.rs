@@ -0,0 +1,124 @@
+//! Cycle-count benchmarks for the Blobstream program. Executes the program for each case in the
+//! baseline and fails if any case's instruction count regresses beyond the tolerance.
+//!
+//! Cases are rows of `benchmarks/baseline.csv`, at heights on Celestia mainnet, so
+//! `TENDERMINT_RPC_URL` must point at a mainnet node that has not pruned them. To add a case, add a
+//! row with an instruction count of 0 and record the baseline with `--update`. Checking a case with
+//! no recorded baseline fails:
+//!
+//! ```
+//! RUST_LOG=info cargo run --bin benchmark --release -- --update
+//! ```
+
+use std::fs;
+
+use blobstream_script::util::TendermintRPCClient;
+use blobstream_script::{TendermintProver, TENDERMINT_ELF};
+use clap::Parser;
+use log::info;
+use primitives::execute_public_values;
+use sp1_sdk::SP1Stdin;
+
+/// The path to the stored baseline of instruction counts.
+const BASELINE_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/benchmarks/baseline.csv");
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct BenchmarkArgs {
+    /// Allowed increase in the instruction count over the baseline, in percent.
+    #[clap(long, default_value = "1")]
+    tolerance_percent: u64,
+
+    /// Overwrite the baseline with the measured instruction counts instead of checking them.
+    #[clap(long)]
+    update: bool,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct BenchmarkCase {
+    name: String,
+    trusted_block: u64,
+    target_block: u64,
+    instruction_count: u64,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv::dotenv().ok();
+    sp1_sdk::utils::setup_logger();
+
+    let args = BenchmarkArgs::parse();
+
+    let mut cases: Vec<BenchmarkCase> = csv::Reader::from_path(BASELINE_PATH)?
+        .deserialize()
+        .collect::<Result<_, _>>()?;
+
+    let tendermint_client = TendermintRPCClient::default();
+    let prover = TendermintProver::new();
+    let mut regressions = Vec::new();
+    let mut missing_baselines = Vec::new();
+    for case in cases.iter_mut() {
+        let inputs = prover
+            .fetch_input_for_blobstream_proof(
+                &tendermint_client,
+                case.trusted_block,
+                case.target_block,
+                &[],
+            )
+            .await?;
+        let expected_public_values = execute_public_values(&inputs)?;
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write_vec(prover.encode_inputs(&inputs));
+        let (public_values, report) = prover.prover_client.execute(TENDERMINT_ELF, stdin).run()?;
+        assert_eq!(
+            public_values.to_vec(),
+            expected_public_values,
+            "The program outputs do not match the natively computed outputs. Check that \
+             elf/blobstream-elf was rebuilt after the last program change."
+        );
+
+        let instruction_count = report.total_instruction_count();
+        info!(
+            "{}: {} instructions (baseline {})",
+            case.name, instruction_count, case.instruction_count
+        );
+
+        if args.update {
+            case.instruction_count = instruction_count;
+        } else if case.instruction_count == 0 {
+            missing_baselines.push(case.name.clone());
+        } else if instruction_count * 100 > case.instruction_count * (100 + args.tolerance_percent)
+        {
+            regressions.push(format!(
+                "{}: {} instructions, baseline {}",
+                case.name, instruction_count, case.instruction_count
+            ));
+        }
+    }
+
+    if args.update {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        for case in &cases {
+            writer.serialize(case)?;
+        }
+        fs::write(BASELINE_PATH, writer.into_inner()?)?;
+        info!("Updated the baseline at {}", BASELINE_PATH);
+    }
+
+    if !missing_baselines.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No baseline recorded for {}, run with --update",
+            missing_baselines.join(", ")
+        ));
+    }
+
+    if !regressions.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Instruction counts regressed:\n{}",
+            regressions.join("\n")
+        ));
+    }
+
+    Ok(())
+}
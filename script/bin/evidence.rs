@@ -0,0 +1,105 @@
+//! Prove a light client attack: two conflicting headers at the same height that are both valid
+//! updates from the same trusted block. The proof can be submitted by the guardian to justify
+//! freezing the SP1 Blobstream contract with `updateFreeze`.
+//!
+//! ```
+//! RUST_LOG=info cargo run --bin evidence --release -- --trusted-block=1 --conflicting-block=5 \
+//!     --conflicting-rpc-url=https://fork.example.com
+//! ```
+
+use alloy::sol_types::SolValue;
+use blobstream_script::evidence::fetch_evidence_inputs;
+use blobstream_script::util::TendermintRPCClient;
+use clap::Parser;
+use primitives::evidence::execute_evidence;
+use primitives::types::VerificationParams;
+use sp1_sdk::{ProverClient, SP1Stdin};
+
+/// The default path to the ELF file of the evidence program. It is not checked in, build it from
+/// `evidence-program` as described in `book/reproducible-builds.md`.
+const EVIDENCE_ELF_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../elf/evidence-elf");
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct EvidenceArgs {
+    /// Trusted block both conflicting blocks are verified from.
+    #[clap(long)]
+    trusted_block: u64,
+
+    /// Height of the conflicting blocks.
+    #[clap(long)]
+    conflicting_block: u64,
+
+    /// Tendermint RPC of a node following the fork. The other conflicting block is fetched from
+    /// TENDERMINT_RPC_URL.
+    #[clap(long)]
+    conflicting_rpc_url: String,
+
+    /// Generate a PLONK proof of the evidence instead of only executing the program.
+    #[clap(long)]
+    prove: bool,
+
+    /// Path to the ELF file of the evidence program.
+    #[clap(long, default_value = EVIDENCE_ELF_PATH)]
+    evidence_elf: String,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv::dotenv().ok();
+    sp1_sdk::utils::setup_logger();
+
+    let args = EvidenceArgs::parse();
+
+    let client = TendermintRPCClient::default();
+    let conflicting_client = TendermintRPCClient::new(args.conflicting_rpc_url);
+    let inputs = fetch_evidence_inputs(
+        &client,
+        &conflicting_client,
+        args.trusted_block,
+        args.conflicting_block,
+        VerificationParams::default(),
+    )
+    .await?;
+
+    // Check the evidence natively before running the program.
+    let expected_outputs = execute_evidence(&inputs)
+        .map_err(|err| anyhow::anyhow!("Invalid light client attack evidence: {}", err))?;
+    println!("Evidence outputs: {:?}", expected_outputs);
+
+    let evidence_elf = std::fs::read(&args.evidence_elf).map_err(|err| {
+        anyhow::anyhow!(
+            "Failed to read the evidence program ELF at {}, build it with `cargo prove build --docker \
+             --tag v3.0.0 --output-directory ../elf --elf-name evidence-elf` from evidence-program: {}",
+            args.evidence_elf,
+            err
+        )
+    })?;
+    let prover_client = ProverClient::new();
+    let mut stdin = SP1Stdin::new();
+    stdin.write_vec(serde_cbor::to_vec(&inputs)?);
+
+    if args.prove {
+        let (pk, vk) = prover_client.setup(&evidence_elf);
+        let proof = prover_client.prove(&pk, stdin).plonk().run()?;
+        prover_client.verify(&proof, &vk)?;
+        println!("Proof: 0x{}", hex::encode(proof.bytes()));
+        println!(
+            "Public values: 0x{}",
+            hex::encode(proof.public_values.to_vec())
+        );
+    } else {
+        let (public_values, report) = prover_client.execute(&evidence_elf, stdin).run()?;
+        assert_eq!(
+            public_values.to_vec(),
+            expected_outputs.abi_encode(),
+            "The program outputs do not match the natively computed outputs."
+        );
+        println!(
+            "Total instruction count: {}",
+            report.total_instruction_count()
+        );
+    }
+
+    Ok(())
+}
@@ -9,6 +9,7 @@
 
 use std::env;
 
+use blobstream_script::provider::LightBlockProvider;
 use blobstream_script::util::TendermintRPCClient;
 use clap::Parser;
 use log::info;
@@ -30,11 +31,22 @@ pub async fn main() {
     let data_fetcher = TendermintRPCClient::default();
     let args = GenesisArgs::parse();
 
+    print_genesis_params(&data_fetcher, args.block).await;
+}
+
+/// Log the genesis parameters of the block, or of the latest block, fetched from the provider.
+async fn print_genesis_params<P: LightBlockProvider + ?Sized>(
+    data_fetcher: &P,
+    block: Option<u64>,
+) {
     let client = ProverClient::new();
     let (_pk, vk) = client.setup(BLOBSTREAMX_ELF);
 
-    if let Some(block) = args.block {
-        let header_hash = data_fetcher.fetch_header_hash(block).await;
+    if let Some(block) = block {
+        let header_hash = data_fetcher
+            .get_header_hash(block)
+            .await
+            .expect("Failed to fetch the genesis header hash");
         info!(
             "\nGENESIS_HEIGHT={:?}\nGENESIS_HEADER={}\nSP1_BLOBSTREAM_PROGRAM_VKEY={}\n",
             block,
@@ -42,8 +54,14 @@ pub async fn main() {
             vk.bytes32(),
         );
     } else {
-        let latest_block_height = data_fetcher.get_latest_block_height().await;
-        let header_hash = data_fetcher.fetch_header_hash(latest_block_height).await;
+        let latest_block_height = data_fetcher
+            .get_latest_block_height()
+            .await
+            .expect("Failed to fetch the latest block height");
+        let header_hash = data_fetcher
+            .get_header_hash(latest_block_height)
+            .await
+            .expect("Failed to fetch the genesis header hash");
 
         info!(
             "\nGENESIS_HEIGHT={:?}\nGENESIS_HEADER={}\nSP1_BLOBSTREAM_PROGRAM_VKEY={}\n",
@@ -10,20 +10,24 @@ use alloy::{
     },
     signers::local::PrivateKeySigner,
     sol,
+    sol_types::SolType,
     transports::http::{Client, Http},
 };
 use anyhow::Result;
+use blobstream_script::error::TendermintRpcError;
+use blobstream_script::provider::LightBlockProvider;
 use blobstream_script::util::TendermintRPCClient;
 use blobstream_script::{relay, TendermintProver};
 use log::{error, info};
-use primitives::get_header_update_verdict;
+use primitives::error::BlobstreamError;
+use primitives::execute_public_values;
+use primitives::types::{InputsEncoding, ProofMode, ProofOutputs, VerificationParams};
 use sp1_sdk::{
     HashableKey, ProverClient, SP1ProofWithPublicValues, SP1ProvingKey, SP1Stdin, SP1VerifyingKey,
 };
 use std::env;
 use std::sync::Arc;
 use std::time::Duration;
-use tendermint_light_client_verifier::Verdict;
 
 const ELF: &[u8] = include_bytes!("../../elf/blobstream-elf");
 
@@ -42,8 +46,10 @@ type EthereumFillProvider = FillProvider<
     Ethereum,
 >;
 
-struct SP1BlobstreamOperator {
+struct SP1BlobstreamOperator<P: LightBlockProvider> {
     client: ProverClient,
+    /// The source of the Tendermint light blocks to prove.
+    provider: P,
     pk: SP1ProvingKey,
     vk: SP1VerifyingKey,
     wallet_filler: Arc<EthereumFillProvider>,
@@ -70,13 +76,17 @@ sol! {
     }
 }
 
+/// Printed when the embedded program disagrees with the native program logic.
+const STALE_ELF_HINT: &str = "Check that elf/blobstream-elf was rebuilt after the last program \
+     change, see book/reproducible-builds.md";
+
 // Timeout for the proof in seconds.
 const PROOF_TIMEOUT_SECONDS: u64 = 60 * 30;
 
 const NUM_RELAY_RETRIES: u32 = 3;
 
-impl SP1BlobstreamOperator {
-    pub async fn new() -> Self {
+impl<P: LightBlockProvider> SP1BlobstreamOperator<P> {
+    pub async fn new(provider: P) -> Self {
         dotenv::dotenv().ok();
 
         let client = ProverClient::new();
@@ -100,18 +110,20 @@ impl SP1BlobstreamOperator {
             .parse()
             .unwrap();
         let signer: PrivateKeySigner = private_key.parse().expect("Failed to parse private key");
+
         let relayer_address = signer.address();
         let wallet = EthereumWallet::from(signer);
-        let provider = ProviderBuilder::new()
+        let eth_provider = ProviderBuilder::new()
             .with_recommended_fillers()
             .wallet(wallet)
             .on_http(rpc_url);
 
         Self {
             client,
+            provider,
             pk,
             vk,
-            wallet_filler: Arc::new(provider),
+            wallet_filler: Arc::new(eth_provider),
             chain_id,
             contract_address,
             relayer_address,
@@ -132,7 +144,9 @@ impl SP1BlobstreamOperator {
             != hex::decode(self.vk.bytes32().strip_prefix("0x").unwrap()).unwrap()
         {
             return Err(anyhow::anyhow!(
-                    "The verifying key in the operator does not match the verifying key in the contract!"
+                    "The verifying key in the operator does not match the verifying key in the contract! \
+                     Rebuild elf/blobstream-elf after program changes and set the new key on the \
+                     contract with updateProgramVkey."
                 ));
         }
 
@@ -143,27 +157,71 @@ impl SP1BlobstreamOperator {
         &self,
         trusted_block: u64,
         target_block: u64,
+        pivot_blocks: &[u64],
+        mode: ProofMode,
     ) -> Result<SP1ProofWithPublicValues> {
-        let prover = TendermintProver::new();
+        let mut prover = TendermintProver::new();
+        prover.verification_params = get_verification_params();
+        prover.commit_header_hashes = env::var("COMMIT_HEADER_HASHES")
+            .unwrap_or("false".to_string())
+            .parse()
+            .expect("invalid COMMIT_HEADER_HASHES");
+        prover.inputs_encoding = get_inputs_encoding();
+        prover.mode = mode;
         let mut stdin = SP1Stdin::new();
 
         let inputs = prover
-            .fetch_input_for_blobstream_proof(trusted_block, target_block)
-            .await;
-
-        // Simulate the step from the trusted block to the target block.
-        let verdict =
-            get_header_update_verdict(&inputs.trusted_light_block, &inputs.target_light_block);
-        assert_eq!(verdict, Verdict::Success);
+            .fetch_input_for_blobstream_proof(
+                &self.provider,
+                trusted_block,
+                target_block,
+                pivot_blocks,
+            )
+            .await?;
+
+        // Execute the program logic natively to check the update is valid before proving.
+        let expected_public_values = execute_public_values(&inputs)?;
+        // Next header outputs do not tally the voting power.
+        if mode != ProofMode::NextHeader {
+            let expected_outputs = ProofOutputs::abi_decode(&expected_public_values, false)?;
+            info!(
+                "Voting power signing block {}: {} of {}",
+                target_block, expected_outputs.signedVotingPower, expected_outputs.totalVotingPower
+            );
+        }
 
-        let encoded_proof_inputs = serde_cbor::to_vec(&inputs)?;
+        let encoded_proof_inputs = prover.encode_inputs(&inputs);
         stdin.write_vec(encoded_proof_inputs);
 
-        self.client
+        // Execute the embedded program before proving, so a stale ELF that cannot decode the inputs
+        // or commits a different layout fails fast instead of after a full proof.
+        let (public_values, _) = self
+            .client
+            .execute(ELF, stdin.clone())
+            .run()
+            .map_err(|e| anyhow::anyhow!("{}. {}", e, STALE_ELF_HINT))?;
+        if public_values.to_vec() != expected_public_values {
+            return Err(anyhow::anyhow!(
+                "The program outputs do not match the natively computed outputs. {}",
+                STALE_ELF_HINT
+            ));
+        }
+
+        let proof = self
+            .client
             .prove(&self.pk, stdin)
             .plonk()
             .timeout(Duration::from_secs(PROOF_TIMEOUT_SECONDS))
-            .run()
+            .run()?;
+
+        // Cross-check the public values of the proof against the natively computed outputs.
+        if proof.public_values.to_vec() != expected_public_values {
+            return Err(anyhow::anyhow!(
+                "The public values of the proof do not match the natively computed outputs!"
+            ));
+        }
+
+        Ok(proof)
     }
 
     /// Relay a header range proof to the SP1 Blobstream contract.
@@ -230,7 +288,7 @@ impl SP1BlobstreamOperator {
     async fn run(&self) -> Result<()> {
         self.check_vkey().await?;
 
-        let fetcher = TendermintRPCClient::default();
+        let fetcher = &self.provider;
         let block_update_interval = get_block_update_interval();
 
         let contract = SP1Blobstream::new(self.contract_address, self.wallet_filler.clone());
@@ -246,7 +304,7 @@ impl SP1BlobstreamOperator {
         let current_block = contract.latestBlock().call().await?.latestBlock;
 
         // Get the head of the chain.
-        let latest_tendermint_block_nb = fetcher.get_latest_block_height().await;
+        let latest_tendermint_block_nb = fetcher.get_latest_block_height().await?;
 
         // Subtract 1 block to ensure the block is stable.
         let latest_stable_tendermint_block = latest_tendermint_block_nb - 1;
@@ -258,20 +316,68 @@ impl SP1BlobstreamOperator {
         );
         let block_to_request = max_block - (max_block % block_update_interval);
 
+        let (mode, block_to_request) = match get_proof_mode() {
+            // In next header mode, step to the block after the current block as soon as it is
+            // stable, instead of waiting for the block update interval. Each proof only advances
+            // the contract by one block, so allow it to fall up to NEXT_HEADER_MAX_LAG blocks
+            // behind the chain while proofs are generated.
+            ProofMode::NextHeader
+                if latest_stable_tendermint_block > current_block
+                    && latest_stable_tendermint_block - current_block
+                        <= get_next_header_max_lag() =>
+            {
+                let target_block = current_block + 1;
+                info!("Attempting to step to the next block {}", target_block);
+                let proof = self
+                    .request_header_range(current_block, target_block, &[], ProofMode::NextHeader)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Next header request failed: {}", e))?;
+                let tx_hash = self.relay_header_range(proof).await?;
+                info!(
+                    "Posted next header {}\nTransaction hash: {}",
+                    target_block, tx_hash
+                );
+                return Ok(());
+            }
+            // When further behind, catch up to the latest stable block with a single range proof
+            // instead of proving one block per loop.
+            ProofMode::NextHeader => (ProofMode::Skipping, max_block),
+            mode => (mode, block_to_request),
+        };
+
         // If block_to_request is greater than the current block in the contract, attempt to request.
         if block_to_request > current_block {
             // The next block the operator should request.
             let max_end_block = block_to_request;
 
-            let target_block = fetcher
-                .find_block_to_request(current_block, max_end_block)
-                .await;
+            // In sequential mode, every block in the range is verified against its own validator
+            // set, so there is no skip to search for. The range is capped instead, as every block
+            // in it is fetched in full and verified in the proof.
+            let (pivot_blocks, target_block) = if mode == ProofMode::Sequential {
+                let target_block =
+                    std::cmp::min(max_end_block, current_block + get_sequential_max_blocks());
+                (Vec::new(), target_block)
+            } else {
+                fetcher
+                    .find_pivots_and_block_to_request(
+                        current_block,
+                        max_end_block,
+                        get_max_pivot_blocks(),
+                    )
+                    .await?
+            };
 
             info!("Current block: {}", current_block);
-            info!("Attempting to step to block {}", target_block);
+            info!(
+                "Attempting to step to block {} through pivots {:?}",
+                target_block, pivot_blocks
+            );
 
             // Request a header range if the target block is not the next block.
-            match self.request_header_range(current_block, target_block).await {
+            match self
+                .request_header_range(current_block, target_block, &pivot_blocks, mode)
+                .await
+            {
                 Ok(proof) => {
                     let tx_hash = self.relay_header_range(proof).await?;
                     info!(
@@ -280,6 +386,30 @@ impl SP1BlobstreamOperator {
                     );
                 }
                 Err(e) => {
+                    // Distinguish a skip that is too large from inputs the program rejects.
+                    if let Some(err) = e.downcast_ref::<BlobstreamError>() {
+                        if err.is_skip_too_large() {
+                            return Err(anyhow::anyhow!(
+                                "Skip from block {} to block {} is too large: {}",
+                                current_block,
+                                target_block,
+                                err
+                            ));
+                        }
+                        return Err(anyhow::anyhow!(
+                            "Invalid inputs for header range from block {} to block {}: {}",
+                            current_block,
+                            target_block,
+                            err
+                        ));
+                    }
+                    if let Some(err) = e.downcast_ref::<TendermintRpcError>() {
+                        return Err(anyhow::anyhow!(
+                            "Tendermint RPC request failed (retryable: {}): {}",
+                            err.is_retryable(),
+                            err
+                        ));
+                    }
                     return Err(anyhow::anyhow!("Header range request failed: {}", e));
                 }
             };
@@ -290,7 +420,16 @@ impl SP1BlobstreamOperator {
     }
 }
 
-fn get_loop_interval_mins() -> u64 {
+/// The interval between operator loops. LOOP_INTERVAL_SECS takes precedence over
+/// LOOP_INTERVAL_MINS, for next header updates that should be relayed within seconds.
+fn get_loop_interval() -> Duration {
+    if let Ok(loop_interval_secs) = env::var("LOOP_INTERVAL_SECS") {
+        return Duration::from_secs(
+            loop_interval_secs
+                .parse::<u64>()
+                .expect("invalid LOOP_INTERVAL_SECS"),
+        );
+    }
     let loop_interval_mins_env = env::var("LOOP_INTERVAL_MINS");
     let mut loop_interval_mins = 60;
     if loop_interval_mins_env.is_ok() {
@@ -299,7 +438,20 @@ fn get_loop_interval_mins() -> u64 {
             .parse::<u64>()
             .expect("invalid LOOP_INTERVAL_MINS");
     }
-    loop_interval_mins
+    Duration::from_secs(60 * loop_interval_mins)
+}
+
+/// In next header mode, the number of blocks the contract may fall behind the latest stable block
+/// before the operator catches up with a range proof instead of a next header proof. Defaults to
+/// 100, around 10 minutes of Celestia blocks.
+fn get_next_header_max_lag() -> u64 {
+    env::var("NEXT_HEADER_MAX_LAG")
+        .map(|next_header_max_lag| {
+            next_header_max_lag
+                .parse::<u64>()
+                .expect("invalid NEXT_HEADER_MAX_LAG")
+        })
+        .unwrap_or(100)
 }
 
 fn get_block_update_interval() -> u64 {
@@ -314,27 +466,116 @@ fn get_block_update_interval() -> u64 {
     block_update_interval
 }
 
+/// The maximum number of pivot blocks a single proof can hop through. Each pivot costs an extra
+/// light client verification in the program. Defaults to 0, which only allows direct skips.
+fn get_max_pivot_blocks() -> usize {
+    env::var("MAX_PIVOT_BLOCKS")
+        .map(|max_pivot_blocks| {
+            max_pivot_blocks
+                .parse::<usize>()
+                .expect("invalid MAX_PIVOT_BLOCKS")
+        })
+        .unwrap_or(0)
+}
+
+/// The maximum number of blocks a single proof covers in sequential mode. Each block costs a full
+/// light client verification in the program, so this bounds the proving time. Defaults to 100.
+fn get_sequential_max_blocks() -> u64 {
+    env::var("SEQUENTIAL_MAX_BLOCKS")
+        .map(|sequential_max_blocks| {
+            sequential_max_blocks
+                .parse::<u64>()
+                .expect("invalid SEQUENTIAL_MAX_BLOCKS")
+        })
+        .unwrap_or(100)
+}
+
+/// Read the proof mode from the environment. Defaults to skipping verification.
+fn get_proof_mode() -> ProofMode {
+    let env_flag = |name: &str| -> bool {
+        env::var(name)
+            .unwrap_or("false".to_string())
+            .parse()
+            .unwrap_or_else(|_| panic!("invalid {}", name))
+    };
+    if env_flag("NEXT_HEADER_UPDATES") {
+        ProofMode::NextHeader
+    } else if env_flag("SEQUENTIAL_VERIFICATION") {
+        ProofMode::Sequential
+    } else {
+        ProofMode::Skipping
+    }
+}
+
+/// Read the encoding of the program inputs from the environment. Defaults to CBOR.
+fn get_inputs_encoding() -> InputsEncoding {
+    match env::var("INPUTS_ENCODING")
+        .unwrap_or_default()
+        .to_lowercase()
+        .as_str()
+    {
+        "" | "cbor" => InputsEncoding::Cbor,
+        "compact" => InputsEncoding::Compact,
+        encoding => panic!(
+            "invalid INPUTS_ENCODING {}, expected cbor or compact",
+            encoding
+        ),
+    }
+}
+
+/// Read the light client verification parameters from the environment. Unset values default to
+/// the Celestia parameters.
+fn get_verification_params() -> VerificationParams {
+    let mut params = VerificationParams::default();
+    if let Ok(trust_threshold) = env::var("TRUST_THRESHOLD") {
+        let (numerator, denominator) = trust_threshold
+            .split_once('/')
+            .expect("invalid TRUST_THRESHOLD, expected a fraction such as 2/3");
+        params.trust_threshold_numerator = numerator.parse().expect("invalid TRUST_THRESHOLD");
+        params.trust_threshold_denominator = denominator.parse().expect("invalid TRUST_THRESHOLD");
+    }
+    if let Ok(trusting_period_secs) = env::var("TRUSTING_PERIOD_SECS") {
+        params.trusting_period_secs = trusting_period_secs
+            .parse()
+            .expect("invalid TRUSTING_PERIOD_SECS");
+    }
+    if let Ok(clock_drift_secs) = env::var("CLOCK_DRIFT_SECS") {
+        params.clock_drift_secs = clock_drift_secs.parse().expect("invalid CLOCK_DRIFT_SECS");
+    }
+    params
+}
+
 #[tokio::main]
 async fn main() {
     dotenv::dotenv().ok();
     env_logger::init();
 
-    let operator = SP1BlobstreamOperator::new().await;
+    // Fail fast if the Tendermint RPC does not serve the expected chain.
+    let tendermint_client = TendermintRPCClient::default();
+    let tendermint_chain_id = env::var("TENDERMINT_CHAIN_ID").expect("TENDERMINT_CHAIN_ID not set");
+    tendermint_client
+        .check_chain_id(&tendermint_chain_id)
+        .await
+        .expect("Tendermint RPC chain ID check failed");
+
+    let operator = SP1BlobstreamOperator::new(tendermint_client).await;
 
     info!("Starting SP1 Blobstream operator");
     const LOOP_TIMEOUT_MINS: u64 = 20;
     loop {
-        let request_interval_mins = get_loop_interval_mins();
+        let request_interval = get_loop_interval();
         // If the operator takes longer than LOOP_TIMEOUT_MINS for a single invocation, or there's
         // an error, sleep for the loop interval and try again.
-        if let Err(e) = tokio::time::timeout(
+        match tokio::time::timeout(
             tokio::time::Duration::from_secs(60 * LOOP_TIMEOUT_MINS),
             operator.run(),
         )
         .await
         {
-            error!("Error running operator: {}", e);
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("Error running operator: {}", e),
+            Err(_) => error!("Operator timed out after {} minutes", LOOP_TIMEOUT_MINS),
         }
-        tokio::time::sleep(tokio::time::Duration::from_secs(60 * request_interval_mins)).await;
+        tokio::time::sleep(request_interval).await;
     }
 }
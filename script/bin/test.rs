@@ -1,6 +1,9 @@
+use blobstream_script::util::TendermintRPCClient;
 use blobstream_script::{TendermintProver, TENDERMINT_ELF};
 use clap::Parser;
 use log::debug;
+use primitives::execute_public_values;
+use primitives::types::{InputsEncoding, ProofMode};
 use sp1_sdk::SP1Stdin;
 use tokio::runtime;
 
@@ -14,6 +17,14 @@ struct ScriptArgs {
     /// Target block.
     #[clap(long, env)]
     target_block: u64,
+
+    /// Pivot blocks to verify the update through, in ascending order.
+    #[clap(long, value_delimiter = ',')]
+    pivot_blocks: Vec<u64>,
+
+    /// Verify every block in the range sequentially instead of skipping through the pivot blocks.
+    #[clap(long)]
+    sequential: bool,
 }
 
 pub async fn get_data_commitment(start_block: u64, end_block: u64) {
@@ -44,27 +55,57 @@ fn main() -> anyhow::Result<()> {
 
     let args = ScriptArgs::parse();
 
-    let prover = TendermintProver::new();
+    let mut prover = TendermintProver::new();
+    if args.sequential {
+        prover.mode = ProofMode::Sequential;
+    }
 
+    let tendermint_client = TendermintRPCClient::default();
     let rt = runtime::Runtime::new()?;
 
-    let mut stdin = SP1Stdin::new();
-
     // Fetch the inputs for the proof.
     let inputs = rt.block_on(async {
         prover
-            .fetch_input_for_blobstream_proof(args.trusted_block, args.target_block)
+            .fetch_input_for_blobstream_proof(
+                &tendermint_client,
+                args.trusted_block,
+                args.target_block,
+                &args.pivot_blocks,
+            )
             .await
-    });
-    let encoded_proof_inputs = serde_cbor::to_vec(&inputs).unwrap();
-    stdin.write_vec(encoded_proof_inputs);
-
-    let (_, report) = prover.prover_client.execute(TENDERMINT_ELF, stdin).run()?;
-    println!("Report: {:?}", report);
-    println!(
-        "Total instruction count: {}",
-        report.total_instruction_count()
-    );
+    })?;
+    // Execute the program logic natively to compute the expected outputs.
+    let expected_public_values = execute_public_values(&inputs).map_err(|err| {
+        if err.is_skip_too_large() {
+            anyhow::anyhow!("Skip is too large, try a closer target block: {}", err)
+        } else {
+            anyhow::anyhow!("Invalid inputs from the Tendermint RPC: {}", err)
+        }
+    })?;
+
+    // Execute the program with each encoding of the inputs to compare their cycle counts.
+    for encoding in [InputsEncoding::Cbor, InputsEncoding::Compact] {
+        let mut stdin = SP1Stdin::new();
+        stdin.write_vec(inputs.encode_as(encoding));
+
+        let (public_values, report) = prover.prover_client.execute(TENDERMINT_ELF, stdin).run()?;
+        assert_eq!(
+            public_values.to_vec(),
+            expected_public_values,
+            "The program outputs do not match the natively computed outputs. Check that \
+             elf/blobstream-elf was rebuilt after the last program change."
+        );
+        println!(
+            "Report ({:?} mode, {:?} inputs): {:?}",
+            prover.mode, encoding, report
+        );
+        println!(
+            "Total instruction count ({:?} mode, {:?} inputs): {}",
+            prover.mode,
+            encoding,
+            report.total_instruction_count()
+        );
+    }
 
     Ok(())
 }
@@ -9,5 +9,21 @@ fn main() {
     //         elf_name: "blobstream-elf".to_string(),
     //         ..Default::default()
     //     },
+    // );
+    // build_program_with_args(
+    //     "../aggregation-program",
+    //     BuildArgs {
+    //         docker: true,
+    //         elf_name: "aggregation-elf".to_string(),
+    //         ..Default::default()
+    //     },
+    // );
+    // build_program_with_args(
+    //     "../evidence-program",
+    //     BuildArgs {
+    //         docker: true,
+    //         elf_name: "evidence-elf".to_string(),
+    //         ..Default::default()
+    //     },
     // )
 }
@@ -0,0 +1,87 @@
+use alloy::primitives::{B256, U256};
+use primitives::bitmap::{get_validator_bitmap, get_validator_bitmap_words, hash_validator_bitmap};
+use tendermint::account::Id as AccountId;
+use tendermint_light_client_verifier::types::LightBlock;
+
+use crate::error::TendermintRpcError;
+use crate::provider::LightBlockProvider;
+
+/// The full validator bitmap committed by a proof from a trusted block to a target block, along
+/// with the trusted validator set it is ordered by.
+#[derive(Debug, Clone)]
+pub struct ValidatorBitmap {
+    /// The `validators_hash` of the trusted header. Matches `trustedValidatorsHash` in the proof
+    /// outputs.
+    pub validators_hash: B256,
+    /// Addresses of the trusted validator set, in bitmap order.
+    pub validators: Vec<AccountId>,
+    /// Whether each validator signed off on both the trusted and target headers.
+    pub signed: Vec<bool>,
+}
+
+impl ValidatorBitmap {
+    /// Rebuild the validator bitmap the program computes for the given light blocks.
+    pub fn from_light_blocks(
+        trusted_light_block: &LightBlock,
+        target_light_block: &LightBlock,
+    ) -> Self {
+        Self {
+            validators_hash: B256::from_slice(
+                trusted_light_block
+                    .signed_header
+                    .header
+                    .validators_hash
+                    .as_bytes(),
+            ),
+            validators: trusted_light_block
+                .validators
+                .validators()
+                .iter()
+                .map(|validator| validator.address)
+                .collect(),
+            signed: get_validator_bitmap(trusted_light_block, target_light_block),
+        }
+    }
+
+    /// The number of validators in the trusted validator set. Matches `validatorCount` in the proof
+    /// outputs.
+    pub fn validator_count(&self) -> u64 {
+        self.signed.len() as u64
+    }
+
+    /// The hash of the full bitmap. Matches `validatorBitmapHash` in the proof outputs.
+    pub fn hash(&self) -> B256 {
+        B256::from(hash_validator_bitmap(&self.signed))
+    }
+
+    /// The bitmap split into U256 words. The first word matches `validatorBitmap` in the proof
+    /// outputs.
+    pub fn words(&self) -> Vec<U256> {
+        get_validator_bitmap_words(&self.signed)
+    }
+
+    /// The addresses of the validators that signed off on both headers.
+    pub fn signers(&self) -> Vec<AccountId> {
+        self.validators
+            .iter()
+            .zip(&self.signed)
+            .filter(|(_, &signed)| signed)
+            .map(|(address, _)| *address)
+            .collect()
+    }
+}
+
+/// Fetch the trusted and target light blocks and rebuild the validator bitmap for a proof between
+/// them.
+pub async fn fetch_validator_bitmap<P: LightBlockProvider>(
+    provider: &P,
+    trusted_block: u64,
+    target_block: u64,
+) -> Result<ValidatorBitmap, TendermintRpcError> {
+    let trusted_light_block = provider.get_light_block(trusted_block).await?;
+    let target_light_block = provider.get_light_block(target_block).await?;
+    Ok(ValidatorBitmap::from_light_blocks(
+        &trusted_light_block,
+        &target_light_block,
+    ))
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use thiserror::Error;
+
+/// Errors returned by the `TendermintRPCClient`.
+#[derive(Debug, Error)]
+pub enum TendermintRpcError {
+    /// The request timed out.
+    #[error("Request to {url} timed out")]
+    Timeout { url: String },
+    /// The request could not be sent, or the response could not be read.
+    #[error("Request to {url} failed: {message}")]
+    Request { url: String, message: String },
+    /// The Tendermint node responded with an HTTP error status, and possibly asked to retry after
+    /// a delay.
+    #[error("Request to {url} failed with HTTP status {status}")]
+    HttpStatus {
+        url: String,
+        status: u16,
+        retry_after: Option<Duration>,
+    },
+    /// The response is not valid JSON for the expected type.
+    #[error("Failed to decode the response from {url}: {message}")]
+    Decode { url: String, message: String },
+    /// The Tendermint node has pruned the block at the height.
+    #[error("Block {height} is pruned by {url}, lowest available height is {lowest_height:?}")]
+    PrunedHeight {
+        url: String,
+        height: u64,
+        lowest_height: Option<u64>,
+    },
+    /// The Tendermint node does not have the block at the height yet.
+    #[error("Block {height} is not available from {url}")]
+    MissingBlock { url: String, height: u64 },
+    /// The Tendermint node returned a JSON-RPC error that is not one of the above.
+    #[error("Request to {url} failed with RPC error {code}: {message} {data}")]
+    Rpc {
+        url: String,
+        code: i64,
+        message: String,
+        data: String,
+    },
+    /// The response decoded, but its contents are invalid.
+    #[error("Invalid response from {url}: {message}")]
+    InvalidResponse { url: String, message: String },
+    /// The Tendermint node serves a different chain than expected.
+    #[error("Tendermint RPC {url} serves chain ID {found}, expected {expected}")]
+    ChainIdMismatch {
+        url: String,
+        expected: String,
+        found: String,
+    },
+    /// Fewer than quorum Tendermint RPC endpoints agree on the light block at the height.
+    #[error(
+        "Only {agreeing} Tendermint RPC endpoints agree on block {height}, quorum is {quorum}"
+    )]
+    QuorumNotReached {
+        height: u64,
+        quorum: usize,
+        agreeing: usize,
+    },
+}
+
+impl TendermintRpcError {
+    /// Whether the request may succeed if it is retried: timeouts, connection failures, rate limits,
+    /// server errors and blocks that the node does not have yet. Other errors will recur.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            TendermintRpcError::Timeout { .. }
+            | TendermintRpcError::Request { .. }
+            | TendermintRpcError::MissingBlock { .. } => true,
+            TendermintRpcError::HttpStatus { status, .. } => *status == 429 || *status >= 500,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use primitives::evidence::EvidenceInputs;
+use primitives::types::VerificationParams;
+
+use crate::error::TendermintRpcError;
+use crate::provider::LightBlockProvider;
+
+/// Fetch the inputs for a proof of a light client attack. The trusted block and the first
+/// conflicting block are fetched from provider, and the second conflicting block, at the same
+/// height, is fetched from conflicting_provider, which follows the fork.
+pub async fn fetch_evidence_inputs<P: LightBlockProvider, C: LightBlockProvider>(
+    provider: &P,
+    conflicting_provider: &C,
+    trusted_block_height: u64,
+    conflicting_block_height: u64,
+    verification_params: VerificationParams,
+) -> Result<EvidenceInputs, TendermintRpcError> {
+    let trusted_light_block = provider.get_light_block(trusted_block_height).await?;
+    let conflicting_light_block_a = provider.get_light_block(conflicting_block_height).await?;
+    let conflicting_light_block_b = conflicting_provider
+        .get_light_block(conflicting_block_height)
+        .await?;
+
+    Ok(EvidenceInputs {
+        trusted_light_block,
+        conflicting_light_block_a,
+        conflicting_light_block_b,
+        verification_params,
+    })
+}
@@ -1,7 +1,16 @@
-use crate::util::TendermintRPCClient;
+use crate::error::TendermintRpcError;
+use crate::provider::LightBlockProvider;
 
-use primitives::types::ProofInputs;
-use sp1_sdk::{ProverClient, SP1ProvingKey, SP1VerifyingKey};
+use primitives::types::{InputsEncoding, ProofInputs, ProofMode, VerificationParams};
+use sp1_sdk::{
+    HashableKey, ProverClient, SP1Proof, SP1ProofWithPublicValues, SP1ProvingKey, SP1Stdin,
+    SP1VerifyingKey,
+};
+pub mod bitmap;
+pub mod error;
+pub mod evidence;
+pub mod merkle;
+pub mod provider;
 pub mod relay;
 mod types;
 pub mod util;
@@ -13,6 +22,14 @@ pub struct TendermintProver {
     pub prover_client: ProverClient,
     pub pkey: SP1ProvingKey,
     pub vkey: SP1VerifyingKey,
+    /// Light client options included in the inputs of every proof.
+    pub verification_params: VerificationParams,
+    /// Whether proofs also commit to the header hashes of the range.
+    pub commit_header_hashes: bool,
+    /// The encoding the inputs are written to the program with.
+    pub inputs_encoding: InputsEncoding,
+    /// How proofs verify the update from the trusted to the target block.
+    pub mode: ProofMode,
 }
 
 impl Default for TendermintProver {
@@ -31,29 +48,216 @@ impl TendermintProver {
             prover_client,
             pkey,
             vkey,
+            verification_params: VerificationParams::default(),
+            commit_header_hashes: false,
+            inputs_encoding: InputsEncoding::default(),
+            mode: ProofMode::default(),
         }
     }
 
-    // Fetch the inputs for a Blobstream proof.
-    pub async fn fetch_input_for_blobstream_proof(
+    /// Encode the inputs to write to the program with the prover's encoding.
+    pub fn encode_inputs(&self, inputs: &ProofInputs) -> Vec<u8> {
+        inputs.encode_as(self.inputs_encoding)
+    }
+
+    // Fetch the inputs for a Blobstream proof from the provider with the prover's settings, see
+    // `fetch_proof_inputs`.
+    pub async fn fetch_input_for_blobstream_proof<P: LightBlockProvider + ?Sized>(
         &self,
+        provider: &P,
         trusted_block_height: u64,
         target_block_height: u64,
-    ) -> ProofInputs {
-        let tendermint_client = TendermintRPCClient::default();
-        let light_blocks = tendermint_client
-            .fetch_light_blocks_in_range(trusted_block_height, target_block_height)
-            .await;
-
-        let mut headers = Vec::new();
-        for light_block in &light_blocks[1..light_blocks.len() - 1] {
-            headers.push(light_block.signed_header.header.clone());
+        pivot_block_heights: &[u64],
+    ) -> Result<ProofInputs, TendermintRpcError> {
+        fetch_proof_inputs(
+            provider,
+            trusted_block_height,
+            target_block_height,
+            pivot_block_heights,
+            self.verification_params,
+            self.commit_header_hashes,
+            self.mode,
+        )
+        .await
+    }
+
+    /// Build the stdin of the aggregation program from compressed range proofs of consecutive
+    /// ranges, ordered by block height. The proofs must have been generated with this prover's
+    /// proving key.
+    pub fn build_aggregation_stdin(
+        &self,
+        range_proofs: &[SP1ProofWithPublicValues],
+    ) -> anyhow::Result<SP1Stdin> {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&self.vkey.hash_u32());
+        let range_public_values: Vec<Vec<u8>> = range_proofs
+            .iter()
+            .map(|proof| proof.public_values.to_vec())
+            .collect();
+        stdin.write(&range_public_values);
+        for range_proof in range_proofs {
+            let SP1Proof::Compressed(proof) = &range_proof.proof else {
+                return Err(anyhow::anyhow!(
+                    "Range proofs must be compressed to be aggregated."
+                ));
+            };
+            stdin.write_proof(*proof.clone(), self.vkey.vk.clone());
         }
+        Ok(stdin)
+    }
+}
 
-        ProofInputs {
-            trusted_light_block: light_blocks[0].clone(),
-            target_light_block: light_blocks[light_blocks.len() - 1].clone(),
-            headers,
+/// Fetch the inputs for a Blobstream proof from the provider. The update is verified through the
+/// pivot blocks, which must be in ascending order and strictly between the trusted and target
+/// blocks. In sequential mode, the pivot blocks are ignored and the update is verified through
+/// every block instead, so every block in the range is fetched in full and callers should bound the
+/// range, as the operator does with `SEQUENTIAL_MAX_BLOCKS`. In next header mode, the target block must be the block after the trusted
+/// block.
+pub async fn fetch_proof_inputs<P: LightBlockProvider + ?Sized>(
+    provider: &P,
+    trusted_block_height: u64,
+    target_block_height: u64,
+    pivot_block_heights: &[u64],
+    verification_params: VerificationParams,
+    commit_header_hashes: bool,
+    mode: ProofMode,
+) -> Result<ProofInputs, TendermintRpcError> {
+    let trusted_light_block = provider.get_light_block(trusted_block_height).await?;
+    let target_light_block = provider.get_light_block(target_block_height).await?;
+
+    // Only the headers of the intermediate blocks are needed, except for the pivot blocks.
+    let (headers, pivot_light_blocks) = match mode {
+        ProofMode::Skipping => {
+            let headers = provider
+                .get_headers_in_range(trusted_block_height + 1, target_block_height - 1)
+                .await?;
+            let pivot_light_blocks = futures::future::try_join_all(
+                pivot_block_heights
+                    .iter()
+                    .map(|height| provider.get_light_block(*height)),
+            )
+            .await?;
+            (headers, pivot_light_blocks)
+        }
+        // The program takes the intermediate headers from the pivot blocks, so they are not
+        // sent twice.
+        ProofMode::Sequential => {
+            let pivot_light_blocks = futures::future::try_join_all(
+                (trusted_block_height + 1..target_block_height)
+                    .map(|height| provider.get_light_block(height)),
+            )
+            .await?;
+            (Vec::new(), pivot_light_blocks)
         }
+        ProofMode::NextHeader => (Vec::new(), Vec::new()),
+    };
+
+    Ok(ProofInputs {
+        trusted_light_block,
+        target_light_block,
+        headers,
+        verification_params,
+        pivot_light_blocks,
+        commit_header_hashes,
+        mode,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::provider::tests::MockProvider;
+
+    use super::*;
+
+    /// The heights of the headers and light blocks in the inputs, in order.
+    fn heights(inputs: &ProofInputs) -> (Vec<u64>, Vec<u64>) {
+        (
+            inputs
+                .headers
+                .iter()
+                .map(|header| header.height.value())
+                .collect(),
+            inputs
+                .pivot_light_blocks
+                .iter()
+                .map(|light_block| light_block.height().value())
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_fetch_proof_inputs_skipping() {
+        let provider = MockProvider::new(10);
+        let params = VerificationParams::default();
+        let inputs =
+            fetch_proof_inputs(&provider, 2, 8, &[4, 6], params, true, ProofMode::Skipping)
+                .await
+                .unwrap();
+
+        assert_eq!(inputs.trusted_light_block.height().value(), 2);
+        assert_eq!(inputs.target_light_block.height().value(), 8);
+        assert_eq!(heights(&inputs), (vec![3, 4, 5, 6, 7], vec![4, 6]));
+        assert_eq!(inputs.verification_params, params);
+        assert!(inputs.commit_header_hashes);
+        assert_eq!(inputs.mode, ProofMode::Skipping);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_proof_inputs_sequential() {
+        let provider = MockProvider::new(10);
+        let inputs = fetch_proof_inputs(
+            &provider,
+            2,
+            6,
+            &[4],
+            VerificationParams::default(),
+            false,
+            ProofMode::Sequential,
+        )
+        .await
+        .unwrap();
+
+        // The pivot blocks are ignored, and every intermediate block is a pivot.
+        assert_eq!(heights(&inputs), (vec![], vec![3, 4, 5]));
+        assert_eq!(inputs.mode, ProofMode::Sequential);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_proof_inputs_next_header() {
+        let provider = MockProvider::new(10);
+        let inputs = fetch_proof_inputs(
+            &provider,
+            2,
+            3,
+            &[],
+            VerificationParams::default(),
+            false,
+            ProofMode::NextHeader,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(inputs.trusted_light_block.height().value(), 2);
+        assert_eq!(inputs.target_light_block.height().value(), 3);
+        assert_eq!(heights(&inputs), (vec![], vec![]));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_proof_inputs_missing_block() {
+        let provider = MockProvider::new(10);
+        let result = fetch_proof_inputs(
+            &provider,
+            8,
+            12,
+            &[],
+            VerificationParams::default(),
+            false,
+            ProofMode::Skipping,
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(TendermintRpcError::MissingBlock { height: 12, .. })
+        ));
     }
 }
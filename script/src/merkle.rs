@@ -0,0 +1,170 @@
+use alloy::primitives::B256;
+use primitives::commitment::{get_split_point, DataCommitmentScheme};
+use tendermint::block::Header;
+
+use crate::error::TendermintRpcError;
+use crate::provider::LightBlockProvider;
+
+/// A Merkle inclusion proof for the Merkle tree of a `DataCommitmentScheme`. Matches the
+/// `BinaryMerkleProof` struct of the Blobstream contracts, so proofs for the `BlobstreamScheme`
+/// can be verified with `BinaryMerkleTree.verify`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryMerkleProof {
+    /// Sibling hashes from the leaf up to the root.
+    pub side_nodes: Vec<B256>,
+    /// Index of the leaf.
+    pub key: u64,
+    /// Number of leaves in the tree.
+    pub num_leaves: u64,
+}
+
+/// An intermediate header opened against the header commitment of a proof.
+#[derive(Debug, Clone)]
+pub struct HeaderInclusionProof {
+    pub header: Header,
+    pub header_hash: B256,
+    pub proof: BinaryMerkleProof,
+}
+
+/// Compute the root of the subtree over the leaves, pushing the side nodes of the leaf at index
+/// from the bottom up.
+fn compute_root_and_side_nodes<S: DataCommitmentScheme>(
+    leaves: &[impl AsRef<[u8]>],
+    index: usize,
+    side_nodes: &mut Vec<B256>,
+) -> [u8; 32] {
+    if leaves.len() == 1 {
+        return S::leaf_hash(leaves[0].as_ref());
+    }
+    let split_point = get_split_point(leaves.len());
+    let (left, right) = leaves.split_at(split_point);
+    if index < split_point {
+        let left_root = compute_root_and_side_nodes::<S>(left, index, side_nodes);
+        let right_root = S::merkle_root(right);
+        side_nodes.push(B256::from(right_root));
+        S::inner_hash(&left_root, &right_root)
+    } else {
+        let left_root = S::merkle_root(left);
+        let right_root = compute_root_and_side_nodes::<S>(right, index - split_point, side_nodes);
+        side_nodes.push(B256::from(left_root));
+        S::inner_hash(&left_root, &right_root)
+    }
+}
+
+/// Generate the inclusion proof of the leaf at index in the scheme's Merkle tree over the leaves.
+/// Returns the root of the tree and the proof.
+pub fn generate_merkle_proof<S: DataCommitmentScheme>(
+    leaves: &[impl AsRef<[u8]>],
+    index: usize,
+) -> (B256, BinaryMerkleProof) {
+    assert!(index < leaves.len(), "Leaf index out of range.");
+    let mut side_nodes = Vec::new();
+    let root = compute_root_and_side_nodes::<S>(leaves, index, &mut side_nodes);
+    (
+        B256::from(root),
+        BinaryMerkleProof {
+            side_nodes,
+            key: index as u64,
+            num_leaves: leaves.len() as u64,
+        },
+    )
+}
+
+/// Open the header at the given height against the header commitment of a proof from trusted_block
+/// to target_block, made with the scheme the program was built with. Returns the header commitment
+/// and the inclusion proof of the header's hash.
+pub async fn generate_header_inclusion_proof<S: DataCommitmentScheme, P: LightBlockProvider>(
+    provider: &P,
+    trusted_block: u64,
+    target_block: u64,
+    height: u64,
+) -> Result<(B256, HeaderInclusionProof), TendermintRpcError> {
+    assert!(
+        trusted_block <= height && height < target_block,
+        "Height must be in [trusted_block, target_block)."
+    );
+    let headers = provider
+        .get_headers_in_range(trusted_block, target_block - 1)
+        .await?;
+    let header_hashes: Vec<B256> = headers
+        .iter()
+        .map(|header| B256::from_slice(header.hash().as_bytes()))
+        .collect();
+
+    let index = (height - trusted_block) as usize;
+    let (header_commitment, proof) = generate_merkle_proof::<S>(&header_hashes, index);
+    Ok((
+        header_commitment,
+        HeaderInclusionProof {
+            header: headers[index].clone(),
+            header_hash: header_hashes[index],
+            proof,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use primitives::commitment::{BlobstreamScheme, KeccakScheme};
+
+    use super::*;
+
+    /// Compute the root from a leaf hash and its proof, as `BinaryMerkleTree.computeRootHash` does
+    /// in the Blobstream contracts: the last side node is the sibling at the top of the tree.
+    fn compute_root_hash<S: DataCommitmentScheme>(
+        key: u64,
+        num_leaves: u64,
+        leaf_hash: [u8; 32],
+        side_nodes: &[B256],
+    ) -> [u8; 32] {
+        if num_leaves == 1 {
+            assert!(side_nodes.is_empty(), "Too many side nodes.");
+            return leaf_hash;
+        }
+        let (sibling, side_nodes) = side_nodes.split_last().expect("Too few side nodes.");
+        let num_left = get_split_point(num_leaves as usize) as u64;
+        if key < num_left {
+            let left = compute_root_hash::<S>(key, num_left, leaf_hash, side_nodes);
+            S::inner_hash(&left, &sibling.0)
+        } else {
+            let right = compute_root_hash::<S>(
+                key - num_left,
+                num_leaves - num_left,
+                leaf_hash,
+                side_nodes,
+            );
+            S::inner_hash(&sibling.0, &right)
+        }
+    }
+
+    fn check_proofs<S: DataCommitmentScheme>() {
+        for num_leaves in [1, 2, 3, 4, 5, 7, 8, 11, 16, 33] {
+            let leaves: Vec<Vec<u8>> = (0..num_leaves)
+                .map(|i| S::encode_leaf(i, [i as u8; 32]))
+                .collect();
+            for index in 0..leaves.len() {
+                let (root, proof) = generate_merkle_proof::<S>(&leaves, index);
+                assert_eq!(root, B256::from(S::merkle_root(&leaves)));
+                assert_eq!(proof.key, index as u64);
+                assert_eq!(proof.num_leaves, num_leaves);
+                let computed_root = compute_root_hash::<S>(
+                    proof.key,
+                    proof.num_leaves,
+                    S::leaf_hash(&leaves[index]),
+                    &proof.side_nodes,
+                );
+                assert_eq!(B256::from(computed_root), root);
+            }
+        }
+    }
+
+    #[test]
+    fn test_merkle_proofs_blobstream_scheme() {
+        check_proofs::<BlobstreamScheme>();
+    }
+
+    #[test]
+    fn test_merkle_proofs_keccak_scheme() {
+        check_proofs::<KeccakScheme>();
+    }
+}
@@ -0,0 +1,219 @@
+use alloy::primitives::B256;
+use async_trait::async_trait;
+use tendermint::block::{Commit, Header};
+use tendermint::validator::Set as ValidatorSet;
+use tendermint_light_client_verifier::types::LightBlock;
+
+use crate::error::TendermintRpcError;
+
+/// A source of Tendermint light blocks for the prover and operator, e.g. a `TendermintRPCClient`
+/// or a mock in tests.
+#[async_trait]
+pub trait LightBlockProvider: Send + Sync {
+    /// The height of the latest block.
+    async fn get_latest_block_height(&self) -> Result<u64, TendermintRpcError>;
+
+    /// The light block at the height.
+    async fn get_light_block(&self, height: u64) -> Result<LightBlock, TendermintRpcError>;
+
+    /// The headers of the blocks from start_height to end_height, inclusive and in order. Empty if
+    /// start_height is greater than end_height.
+    async fn get_headers_in_range(
+        &self,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<Vec<Header>, TendermintRpcError>;
+
+    /// The validator set of the block at the height.
+    async fn get_validators(&self, height: u64) -> Result<ValidatorSet, TendermintRpcError>;
+
+    /// The hash of the header at the height.
+    async fn get_header_hash(&self, height: u64) -> Result<B256, TendermintRpcError> {
+        let light_block = self.get_light_block(height).await?;
+        Ok(B256::from_slice(
+            light_block.signed_header.header.hash().as_bytes(),
+        ))
+    }
+
+    /// Search to find the greatest block number to request.
+    async fn find_block_to_request(
+        &self,
+        start_block: u64,
+        max_end_block: u64,
+    ) -> Result<u64, TendermintRpcError> {
+        let mut curr_end_block = max_end_block;
+        loop {
+            if curr_end_block - start_block == 1 {
+                return Ok(curr_end_block);
+            }
+            let start_validator_set = self.get_validators(start_block).await?;
+            let target_light_block = self.get_light_block(curr_end_block).await?;
+            if is_valid_skip(
+                start_validator_set,
+                target_light_block.validators,
+                target_light_block.signed_header.commit,
+            ) {
+                return Ok(curr_end_block);
+            }
+            let mid_block = (curr_end_block + start_block) / 2;
+            curr_end_block = mid_block;
+        }
+    }
+
+    /// Search for a target block as close to max_end_block as possible, hopping through at most
+    /// max_pivots intermediate blocks that are each a valid skip from the previous one. Returns the
+    /// pivot block heights in order and the target block.
+    async fn find_pivots_and_block_to_request(
+        &self,
+        start_block: u64,
+        max_end_block: u64,
+        max_pivots: usize,
+    ) -> Result<(Vec<u64>, u64), TendermintRpcError> {
+        let mut pivots = Vec::new();
+        let mut curr_block = self
+            .find_block_to_request(start_block, max_end_block)
+            .await?;
+        while curr_block != max_end_block && pivots.len() < max_pivots {
+            pivots.push(curr_block);
+            curr_block = self
+                .find_block_to_request(curr_block, max_end_block)
+                .await?;
+        }
+        Ok((pivots, curr_block))
+    }
+}
+
+/// Determines if a valid skip is possible between start_block and target_block.
+pub fn is_valid_skip(
+    start_validator_set: ValidatorSet,
+    target_validator_set: ValidatorSet,
+    target_block_commit: Commit,
+) -> bool {
+    let threshold = 1_f64 / 3_f64;
+    let mut shared_voting_power = 0_u64;
+    let target_block_total_voting_power = target_validator_set.total_voting_power().value();
+    let start_block_validators = start_validator_set.validators();
+    let mut start_block_idx = 0;
+    let start_block_num_validators = start_block_validators.len();
+
+    // Exit if we have already reached the threshold
+    while (target_block_total_voting_power as f64) * threshold > shared_voting_power as f64
+        && start_block_idx < start_block_num_validators
+    {
+        if let Some(target_block_validator) =
+            target_validator_set.validator(start_block_validators[start_block_idx].address)
+        {
+            // Confirm that the validator has signed on target_block.
+            for sig in target_block_commit.signatures.iter() {
+                if let Some(validator_address) = sig.validator_address() {
+                    if validator_address == target_block_validator.address {
+                        // Add the shared voting power to the validator
+                        shared_voting_power += target_block_validator.power.value();
+                    }
+                }
+            }
+        }
+        start_block_idx += 1;
+    }
+
+    (target_block_total_voting_power as f64) * threshold <= shared_voting_power as f64
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::BTreeMap;
+
+    use tendermint::block::{self, header::Version, signed_header::SignedHeader, Height};
+    use tendermint::{account, chain, node, AppHash, Hash, Time};
+
+    use super::*;
+
+    /// An in-memory source of light blocks, for testing code that takes a `LightBlockProvider`.
+    /// The light blocks are not signed, so they only pass decoding.
+    pub(crate) struct MockProvider {
+        light_blocks: BTreeMap<u64, LightBlock>,
+    }
+
+    impl MockProvider {
+        /// A provider of the light blocks from height 1 to latest_height.
+        pub(crate) fn new(latest_height: u64) -> Self {
+            Self {
+                light_blocks: (1..=latest_height)
+                    .map(|height| (height, mock_light_block(height)))
+                    .collect(),
+            }
+        }
+    }
+
+    /// An unsigned light block at the height, with an empty validator set.
+    fn mock_light_block(height: u64) -> LightBlock {
+        let validators = ValidatorSet::without_proposer(Vec::new());
+        let header = Header {
+            version: Version { block: 11, app: 1 },
+            chain_id: "mock-chain".parse::<chain::Id>().unwrap(),
+            height: Height::try_from(height).unwrap(),
+            time: Time::from_unix_timestamp(1_700_000_000 + height as i64, 0).unwrap(),
+            last_block_id: None,
+            last_commit_hash: None,
+            data_hash: Some(Hash::Sha256([height as u8; 32])),
+            validators_hash: validators.hash(),
+            next_validators_hash: validators.hash(),
+            consensus_hash: Hash::Sha256([1; 32]),
+            app_hash: AppHash::try_from(vec![2; 32]).unwrap(),
+            last_results_hash: None,
+            evidence_hash: None,
+            proposer_address: account::Id::new([0; 20]),
+        };
+        let commit = Commit {
+            height: header.height,
+            round: block::Round::default(),
+            block_id: block::Id {
+                hash: header.hash(),
+                part_set_header: block::parts::Header::new(1, Hash::Sha256([3; 32])).unwrap(),
+            },
+            signatures: Vec::new(),
+        };
+        LightBlock::new(
+            SignedHeader::new(header, commit).unwrap(),
+            validators.clone(),
+            validators,
+            node::Id::new([0; 20]),
+        )
+    }
+
+    #[async_trait]
+    impl LightBlockProvider for MockProvider {
+        async fn get_latest_block_height(&self) -> Result<u64, TendermintRpcError> {
+            Ok(self
+                .light_blocks
+                .last_key_value()
+                .map_or(0, |(height, _)| *height))
+        }
+
+        async fn get_light_block(&self, height: u64) -> Result<LightBlock, TendermintRpcError> {
+            self.light_blocks
+                .get(&height)
+                .cloned()
+                .ok_or(TendermintRpcError::MissingBlock {
+                    url: "mock".to_string(),
+                    height,
+                })
+        }
+
+        async fn get_headers_in_range(
+            &self,
+            start_height: u64,
+            end_height: u64,
+        ) -> Result<Vec<Header>, TendermintRpcError> {
+            let mut headers = Vec::new();
+            for height in start_height..=end_height {
+                headers.push(self.get_light_block(height).await?.signed_header.header);
+            }
+            Ok(headers)
+        }
+
+        async fn get_validators(&self, height: u64) -> Result<ValidatorSet, TendermintRpcError> {
+            Ok(self.get_light_block(height).await?.validators)
+        }
+    }
+}
@@ -18,6 +18,7 @@ pub struct PeerIdWrapper {
 #[derive(Debug, Deserialize)]
 pub struct NodeInfoWrapper {
     pub id: String,
+    pub network: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -42,6 +43,21 @@ pub struct SignedHeaderWrapper {
     pub signed_header: SignedHeader,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct BlockchainResponse {
+    pub result: BlockchainInfo,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BlockchainInfo {
+    pub block_metas: Vec<BlockMeta>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BlockMeta {
+    pub header: block::Header,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ValidatorSetResponse {
     pub result: BlockValidatorSet,
@@ -55,3 +71,16 @@ pub struct BlockValidatorSet {
     pub count: String,
     pub total: String,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct RpcErrorResponse {
+    pub error: Option<RpcErrorBody>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RpcErrorBody {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: String,
+}
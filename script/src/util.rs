@@ -1,37 +1,141 @@
 #![allow(dead_code)]
+use crate::error::TendermintRpcError;
+use crate::provider::LightBlockProvider;
 use crate::types::*;
-use alloy::primitives::B256;
-use log::debug;
-use reqwest::Client;
+use async_trait::async_trait;
+use log::{debug, warn};
+use reqwest::{header::RETRY_AFTER, Client};
+use serde::de::DeserializeOwned;
+use std::future::Future;
 use std::sync::Arc;
-use std::{collections::HashMap, env, error::Error};
+use std::time::Duration;
+use std::{collections::HashMap, env};
 use subtle_encoding::hex;
-use tendermint::block::Commit;
-use tendermint::validator::Set as TendermintValidatorSet;
 use tendermint::{
-    block::signed_header::SignedHeader,
+    block::{signed_header::SignedHeader, Header},
     node::Id,
     validator::{Info, Set},
 };
 use tendermint_light_client_verifier::types::{LightBlock, ValidatorSet};
+use tokio::sync::{OnceCell, Semaphore};
 
 pub struct TendermintRPCClient {
-    url: String,
+    /// The Tendermint RPC endpoints, in order of preference.
+    urls: Vec<String>,
+    /// The number of endpoints that must agree on a light block before it is used.
+    quorum: usize,
     client: Arc<Client>,
+    retry_policy: RetryPolicy,
+    /// Limits the number of requests in flight across all clones of the limiter.
+    request_limiter: Arc<Semaphore>,
+    /// The peer ID of the first endpoint that responded, fetched once.
+    peer_id: OnceCell<[u8; 20]>,
+}
+
+/// How failed Tendermint RPC requests are retried. Only errors that are retryable according to
+/// `TendermintRpcError::is_retryable` are retried.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// The maximum number of retries after the first attempt.
+    pub max_retries: u32,
+    /// The backoff before the first retry. The backoff doubles after every retry.
+    pub initial_backoff: Duration,
+    /// The maximum backoff between retries.
+    pub max_backoff: Duration,
+    /// The maximum wait when the server asks to retry after a given duration with a `Retry-After`
+    /// header. This is separate from `max_backoff`, as rate limited endpoints often ask for longer
+    /// waits than the exponential backoff reaches.
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_retry_after: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The backoff before the given retry (starting at 0): exponential backoff with full jitter,
+    /// unless the server asked to retry after a given duration.
+    fn backoff(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        if let Some(retry_after) = retry_after {
+            return retry_after.min(self.max_retry_after);
+        }
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff);
+        backoff.mul_f64(rand::random::<f64>())
+    }
+}
+
+/// Parses the value of a `Retry-After` header, which is either a number of seconds or an HTTP date
+/// to retry after. A date in the past means retrying immediately.
+fn parse_retry_after(retry_after: &str) -> Option<Duration> {
+    let retry_after = retry_after.trim();
+    if let Ok(secs) = retry_after.parse() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(retry_after).ok()?;
+    Some(
+        (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
 }
 
 impl Default for TendermintRPCClient {
     fn default() -> Self {
-        let url = env::var("TENDERMINT_RPC_URL").expect("TENDERMINT_RPC_URL not set");
-        Self::new(url)
+        let urls = env::var("TENDERMINT_RPC_URL").expect("TENDERMINT_RPC_URL not set");
+        let mut client = Self::new_with_endpoints(
+            urls.split(',')
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty())
+                .collect(),
+        );
+        if let Ok(max_retries) = env::var("TENDERMINT_RPC_MAX_RETRIES") {
+            client.retry_policy.max_retries = max_retries
+                .parse()
+                .expect("invalid TENDERMINT_RPC_MAX_RETRIES");
+        }
+        if let Ok(max_concurrent_requests) = env::var("TENDERMINT_RPC_MAX_CONCURRENT_REQUESTS") {
+            client = client.with_max_concurrent_requests(
+                max_concurrent_requests
+                    .parse()
+                    .expect("invalid TENDERMINT_RPC_MAX_CONCURRENT_REQUESTS"),
+            );
+        }
+        if let Ok(quorum) = env::var("TENDERMINT_RPC_QUORUM") {
+            client = client.with_quorum(quorum.parse().expect("invalid TENDERMINT_RPC_QUORUM"));
+        }
+        client
     }
 }
 
 /// The default timeout for Tendermint RPC requests in seconds.
 const DEFAULT_TENDERMINT_RPC_TIMEOUT_SECS: u64 = 20;
 
+/// The maximum number of headers the Tendermint RPC returns for a `/blockchain` request.
+const MAX_BLOCKCHAIN_HEADERS: u64 = 20;
+
+/// The default maximum number of Tendermint RPC requests in flight.
+const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 25;
+
 impl TendermintRPCClient {
     pub fn new(url: String) -> Self {
+        Self::new_with_endpoints(vec![url])
+    }
+
+    /// Creates a client for several endpoints of the same chain, in order of preference. Each fetch
+    /// is made against a single endpoint and fails over to the next endpoint as a whole when it
+    /// fails, so the commit and validator pages of a light block never mix endpoints.
+    pub fn new_with_endpoints(urls: Vec<String>) -> Self {
+        assert!(!urls.is_empty(), "no Tendermint RPC endpoints");
         let client = Client::builder()
             .timeout(std::time::Duration::from_secs(
                 DEFAULT_TENDERMINT_RPC_TIMEOUT_SECS,
@@ -39,66 +143,68 @@ impl TendermintRPCClient {
             .build()
             .unwrap();
         TendermintRPCClient {
-            url,
+            urls,
+            quorum: 1,
             client: Arc::new(client),
+            retry_policy: RetryPolicy::default(),
+            request_limiter: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_REQUESTS)),
+            peer_id: OnceCell::new(),
         }
     }
 
-    // Search to find the greatest block number to request.
-    pub async fn find_block_to_request(&self, start_block: u64, max_end_block: u64) -> u64 {
-        let mut curr_end_block = max_end_block;
-        loop {
-            if curr_end_block - start_block == 1 {
-                return curr_end_block;
-            }
-            let start_block_validators = self.fetch_validators(start_block).await.unwrap();
-            let start_validator_set = Set::new(start_block_validators, None);
-            let target_block_validators = self.fetch_validators(curr_end_block).await.unwrap();
-            let target_validator_set = Set::new(target_block_validators, None);
-            let target_block_commit = self.fetch_commit(curr_end_block).await.unwrap();
-            if Self::is_valid_skip(
-                start_validator_set,
-                target_validator_set,
-                target_block_commit.result.signed_header.commit,
-            ) {
-                return curr_end_block;
-            }
-            let mid_block = (curr_end_block + start_block) / 2;
-            curr_end_block = mid_block;
-        }
+    /// Sets the policy for retrying failed requests.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Requires quorum endpoints to agree on the header hash and validator sets of each light block
+    /// before it is used, so that a single malicious or buggy endpoint cannot waste a proof.
+    pub fn with_quorum(mut self, quorum: usize) -> Self {
+        assert!(
+            (1..=self.urls.len()).contains(&quorum),
+            "quorum must be between 1 and the number of endpoints"
+        );
+        self.quorum = quorum;
+        self
     }
 
-    /// Fetches all light blocks for the given range of block heights. Inclusive of start and end.
-    pub async fn fetch_light_blocks_in_range(
+    /// Sets the maximum number of requests in flight.
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.request_limiter = Arc::new(Semaphore::new(max_concurrent_requests));
+        self
+    }
+
+    /// Shares the request limiter of another client, so that together they stay under a single
+    /// limit, e.g. for clients of the same RPC provider.
+    pub fn with_request_limiter(mut self, request_limiter: Arc<Semaphore>) -> Self {
+        self.request_limiter = request_limiter;
+        self
+    }
+
+    /// Fetches the headers for the given range of block heights, inclusive of start and end. Each
+    /// `/blockchain` request returns up to 20 headers without their commits or validator sets, so
+    /// this is much cheaper than fetching the light blocks. The headers are not checked against a
+    /// quorum of endpoints, as the program checks that they link the trusted and target blocks.
+    pub async fn fetch_headers_in_range(
         &self,
         start_height: u64,
         end_height: u64,
-    ) -> Vec<LightBlock> {
-        let peer_id = self.fetch_peer_id().await.unwrap();
-        let batch_size = 25;
-        let mut blocks = Vec::new();
+    ) -> Result<Vec<Header>, TendermintRpcError> {
         debug!(
-            "Fetching light blocks in range: {} to {}",
+            "Fetching headers in range: {} to {}",
             start_height, end_height
         );
-
-        for batch_start in (start_height..=end_height).step_by(batch_size) {
-            let batch_end = std::cmp::min(batch_start + (batch_size as u64) - 1, end_height);
-            let mut handles = Vec::new();
-
-            for height in batch_start..=batch_end {
-                let fetch_light_block =
-                    async move { self.fetch_light_block(height, peer_id).await.unwrap() };
-                handles.push(fetch_light_block);
-            }
-
-            // Join all the futures in the current batch
-            let batch_blocks = futures::future::join_all(handles).await;
-            blocks.extend(batch_blocks);
-        }
-
-        debug!("Finished fetching light blocks!");
-        blocks
+        let handles = (start_height..=end_height)
+            .step_by(MAX_BLOCKCHAIN_HEADERS as usize)
+            .map(|batch_start| {
+                let batch_end = std::cmp::min(batch_start + MAX_BLOCKCHAIN_HEADERS - 1, end_height);
+                self.with_failover(move |index| {
+                    self.fetch_blockchain_headers(index, batch_start, batch_end)
+                })
+            });
+        let batches = futures::future::try_join_all(handles).await?;
+        Ok(batches.into_iter().flatten().collect())
     }
 
     /// Retrieves light blocks for the trusted and target block heights.
@@ -106,30 +212,20 @@ impl TendermintRPCClient {
         &self,
         trusted_block_height: u64,
         target_block_height: u64,
-    ) -> (LightBlock, LightBlock) {
-        let peer_id = self.fetch_peer_id().await.unwrap();
+    ) -> Result<(LightBlock, LightBlock), TendermintRpcError> {
+        let peer_id = self.fetch_peer_id().await?;
 
         let trusted_light_block = self
             .fetch_light_block(trusted_block_height, peer_id)
-            .await
-            .expect("Failed to generate light block 1");
-        let target_light_block = self
-            .fetch_light_block(target_block_height, peer_id)
-            .await
-            .expect("Failed to generate light block 2");
-        (trusted_light_block, target_light_block)
-    }
-
-    /// Retrieves the latest block height from the Tendermint node.
-    pub async fn get_latest_block_height(&self) -> u64 {
-        let latest_commit = self.fetch_latest_commit().await.unwrap();
-        latest_commit.result.signed_header.header.height.value()
+            .await?;
+        let target_light_block = self.fetch_light_block(target_block_height, peer_id).await?;
+        Ok((trusted_light_block, target_light_block))
     }
 
     /// Retrieves the block height from a given block hash.
-    pub async fn get_block_height_from_hash(&self, hash: &[u8]) -> u64 {
-        let block = self.fetch_block_by_hash(hash).await.unwrap();
-        block.result.block.header.height.value()
+    pub async fn get_block_height_from_hash(&self, hash: &[u8]) -> Result<u64, TendermintRpcError> {
+        let block = self.fetch_block_by_hash(hash).await?;
+        Ok(block.result.block.header.height.value())
     }
 
     /// Sorts the signatures in the signed header based on the descending order of validators' power.
@@ -157,110 +253,333 @@ impl TendermintRPCClient {
         });
     }
 
-    /// Fetches the peer ID from the Tendermint node.
-    async fn fetch_peer_id(&self) -> Result<[u8; 20], Box<dyn Error>> {
-        let fetch_peer_id_url = format!("{}/status", self.url);
+    /// Runs the fetch against the endpoint at each index in order of preference, until it succeeds
+    /// on one. Returns the error from the last endpoint if it fails on all of them.
+    async fn with_failover<T, F, Fut>(&self, fetch: F) -> Result<T, TendermintRpcError>
+    where
+        F: Fn(usize) -> Fut,
+        Fut: Future<Output = Result<T, TendermintRpcError>>,
+    {
+        let mut result = fetch(0).await;
+        for index in 1..self.urls.len() {
+            let Err(err) = &result else { break };
+            warn!("Failing over to {} after error: {}", self.urls[index], err);
+            result = fetch(index).await;
+        }
+        result
+    }
+
+    /// Sends a GET request for the route to the endpoint at the index and decodes the JSON
+    /// response, retrying retryable errors according to the retry policy. The height the request is
+    /// for, if any, is used to classify pruned and missing block errors.
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        index: usize,
+        route: &str,
+        query: &[(&str, String)],
+        height: Option<u64>,
+    ) -> Result<T, TendermintRpcError> {
+        let mut retry = 0;
+        loop {
+            // Hold a permit only while the request is in flight, not while backing off.
+            let result = {
+                let _permit = self
+                    .request_limiter
+                    .acquire()
+                    .await
+                    .expect("request limiter closed");
+                self.try_get_json(&self.urls[index], route, query, height)
+                    .await
+            };
+            match result {
+                Err(err) if err.is_retryable() && retry < self.retry_policy.max_retries => {
+                    let retry_after = match &err {
+                        TendermintRpcError::HttpStatus { retry_after, .. } => *retry_after,
+                        _ => None,
+                    };
+                    let backoff = self.retry_policy.backoff(retry, retry_after);
+                    warn!(
+                        "Retrying in {:?} after error ({} of {}): {}",
+                        backoff,
+                        retry + 1,
+                        self.retry_policy.max_retries,
+                        err
+                    );
+                    tokio::time::sleep(backoff).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends a single GET request for the route of the Tendermint RPC and decodes the JSON response.
+    async fn try_get_json<T: DeserializeOwned>(
+        &self,
+        base_url: &str,
+        route: &str,
+        query: &[(&str, String)],
+        height: Option<u64>,
+    ) -> Result<T, TendermintRpcError> {
+        let url = format!("{}/{}", base_url, route);
+        let request_error = |err: reqwest::Error| {
+            if err.is_timeout() {
+                TendermintRpcError::Timeout { url: url.clone() }
+            } else {
+                TendermintRpcError::Request {
+                    url: url.clone(),
+                    message: err.to_string(),
+                }
+            }
+        };
 
-        let response: PeerIdResponse = self
+        let response = self
             .client
-            .get(fetch_peer_id_url)
+            .get(&url)
+            .query(query)
             .send()
-            .await?
-            .json::<PeerIdResponse>()
-            .await?;
+            .await
+            .map_err(request_error)?;
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|retry_after| retry_after.to_str().ok())
+            .and_then(parse_retry_after);
+        let body = response.text().await.map_err(request_error)?;
+
+        // Tendermint returns JSON-RPC errors with an HTTP error status, so check for them first.
+        if let Ok(RpcErrorResponse { error: Some(error) }) = serde_json::from_str(&body) {
+            return Err(Self::classify_rpc_error(url, height, error));
+        }
+        if !status.is_success() {
+            return Err(TendermintRpcError::HttpStatus {
+                url,
+                status: status.as_u16(),
+                retry_after,
+            });
+        }
+        serde_json::from_str(&body).map_err(|err| TendermintRpcError::Decode {
+            url,
+            message: err.to_string(),
+        })
+    }
+
+    /// Classifies a JSON-RPC error returned for a request for the block at height.
+    fn classify_rpc_error(
+        url: String,
+        height: Option<u64>,
+        error: RpcErrorBody,
+    ) -> TendermintRpcError {
+        if let Some(height) = height {
+            // e.g. "height 100 is not available, lowest height is 2000"
+            if let Some((_, lowest_height)) = error.data.split_once("lowest height is ") {
+                return TendermintRpcError::PrunedHeight {
+                    url,
+                    height,
+                    lowest_height: lowest_height.trim().parse().ok(),
+                };
+            }
+            // e.g. "height 3000 must be less than or equal to the current blockchain height 2999"
+            if error
+                .data
+                .contains("must be less than or equal to the current blockchain height")
+            {
+                return TendermintRpcError::MissingBlock { url, height };
+            }
+        }
+        TendermintRpcError::Rpc {
+            url,
+            code: error.code,
+            message: error.message,
+            data: error.data,
+        }
+    }
+
+    /// Fetches the peer ID from the Tendermint node, or returns the cached peer ID.
+    async fn fetch_peer_id(&self) -> Result<[u8; 20], TendermintRpcError> {
+        self.peer_id
+            .get_or_try_init(|| self.fetch_peer_id_uncached())
+            .await
+            .copied()
+    }
+
+    async fn fetch_peer_id_uncached(&self) -> Result<[u8; 20], TendermintRpcError> {
+        self.with_failover(|index| self.fetch_peer_id_from(index))
+            .await
+    }
+
+    async fn fetch_peer_id_from(&self, index: usize) -> Result<[u8; 20], TendermintRpcError> {
+        let response: PeerIdResponse = self.get_json(index, "status", &[], None).await?;
+
+        hex::decode(&response.result.node_info.id)
+            .ok()
+            .and_then(|peer_id| peer_id.try_into().ok())
+            .ok_or_else(|| TendermintRpcError::InvalidResponse {
+                url: format!("{}/status", self.urls[index]),
+                message: format!("invalid peer ID {}", response.result.node_info.id),
+            })
+    }
+
+    /// Fetches the chain ID (network) of the Tendermint node.
+    pub async fn fetch_chain_id(&self) -> Result<String, TendermintRpcError> {
+        self.with_failover(|index| self.fetch_chain_id_from(index))
+            .await
+    }
+
+    async fn fetch_chain_id_from(&self, index: usize) -> Result<String, TendermintRpcError> {
+        let response: PeerIdResponse = self.get_json(index, "status", &[], None).await?;
+
+        Ok(response.result.node_info.network)
+    }
 
-        Ok(hex::decode(response.result.node_info.id)
-            .unwrap()
-            .try_into()
-            .unwrap())
+    /// Checks that every Tendermint endpoint serves the expected chain ID. Use this on startup to
+    /// catch a misconfigured RPC URL before any proofs are requested.
+    pub async fn check_chain_id(&self, expected_chain_id: &str) -> Result<(), TendermintRpcError> {
+        for (index, url) in self.urls.iter().enumerate() {
+            let chain_id = self.fetch_chain_id_from(index).await?;
+            if chain_id != expected_chain_id {
+                return Err(TendermintRpcError::ChainIdMismatch {
+                    url: url.clone(),
+                    expected: expected_chain_id.to_string(),
+                    found: chain_id,
+                });
+            }
+        }
+        Ok(())
     }
 
     /// Fetches a block by its hash.
-    async fn fetch_block_by_hash(&self, hash: &[u8]) -> Result<BlockResponse, Box<dyn Error>> {
-        let block_by_hash_url = format!(
-            "{}/block_by_hash?hash=0x{}",
-            self.url,
-            String::from_utf8(hex::encode(hash)).unwrap()
-        );
-        let response: BlockResponse = self
-            .client
-            .get(block_by_hash_url)
-            .send()
-            .await?
-            .json::<BlockResponse>()
-            .await?;
-        Ok(response)
+    async fn fetch_block_by_hash(&self, hash: &[u8]) -> Result<BlockResponse, TendermintRpcError> {
+        let hash = format!("0x{}", String::from_utf8(hex::encode(hash)).unwrap());
+        let query = [("hash", hash)];
+        self.with_failover(|index| self.get_json(index, "block_by_hash", &query, None))
+            .await
     }
 
     /// Fetches a light block by its hash.
-    async fn get_light_block_by_hash(&self, hash: &[u8]) -> LightBlock {
-        let block = self.fetch_block_by_hash(hash).await.unwrap();
-        let peer_id = self.fetch_peer_id().await.unwrap();
-        self.fetch_light_block(
-            block.result.block.header.height.value(),
-            hex::decode(peer_id).unwrap().try_into().unwrap(),
-        )
-        .await
-        .unwrap()
+    async fn get_light_block_by_hash(&self, hash: &[u8]) -> Result<LightBlock, TendermintRpcError> {
+        let block = self.fetch_block_by_hash(hash).await?;
+        let peer_id = self.fetch_peer_id().await?;
+        self.fetch_light_block(block.result.block.header.height.value(), peer_id)
+            .await
     }
 
     /// Fetches the latest commit from the Tendermint node.
-    async fn fetch_latest_commit(&self) -> Result<CommitResponse, Box<dyn Error>> {
-        let url = format!("{}/commit", self.url);
-
-        let response: CommitResponse = self
-            .client
-            .get(url)
-            .send()
-            .await?
-            .json::<CommitResponse>()
-            .await?;
-        Ok(response)
+    async fn fetch_latest_commit(&self) -> Result<CommitResponse, TendermintRpcError> {
+        self.with_failover(|index| self.get_json(index, "commit", &[], None))
+            .await
     }
 
-    /// Fetches a commit for a specific block height.
-    async fn fetch_commit(&self, block_height: u64) -> Result<CommitResponse, Box<dyn Error>> {
-        let url = format!("{}/{}", self.url, "commit");
-
-        let response: CommitResponse = self
-            .client
-            .get(url)
-            .query(&[
-                ("height", block_height.to_string().as_str()),
-                ("per_page", "100"), // helpful only when fetching validators
-            ])
-            .send()
-            .await?
-            .json::<CommitResponse>()
+    /// Fetches the headers from min_height to max_height, which must be at most
+    /// `MAX_BLOCKCHAIN_HEADERS` apart, in ascending order from the endpoint at the index.
+    async fn fetch_blockchain_headers(
+        &self,
+        index: usize,
+        min_height: u64,
+        max_height: u64,
+    ) -> Result<Vec<Header>, TendermintRpcError> {
+        let response: BlockchainResponse = self
+            .get_json(
+                index,
+                "blockchain",
+                &[
+                    ("minHeight", min_height.to_string()),
+                    ("maxHeight", max_height.to_string()),
+                ],
+                Some(max_height),
+            )
             .await?;
-        Ok(response)
+        // The block metas are in descending order of height.
+        let headers: Vec<Header> = response
+            .result
+            .block_metas
+            .into_iter()
+            .rev()
+            .map(|block_meta| block_meta.header)
+            .collect();
+
+        // The node clamps the range to the heights it has, rather than returning an error.
+        let heights: Vec<u64> = headers.iter().map(|header| header.height.value()).collect();
+        if heights.last().map_or(true, |height| *height < max_height) {
+            return Err(TendermintRpcError::MissingBlock {
+                url: format!("{}/blockchain", self.urls[index]),
+                height: max_height,
+            });
+        }
+        if !heights.iter().copied().eq(min_height..=max_height) {
+            return Err(TendermintRpcError::InvalidResponse {
+                url: format!("{}/blockchain", self.urls[index]),
+                message: format!(
+                    "expected headers {} to {}, got heights {:?}",
+                    min_height, max_height, heights
+                ),
+            });
+        }
+        Ok(headers)
     }
 
-    /// Fetches validators for a specific block height.
-    async fn fetch_validators(&self, block_height: u64) -> Result<Vec<Info>, Box<dyn Error>> {
-        let url = format!("{}/{}", self.url, "validators");
+    /// Fetches a commit for a specific block height from the endpoint at the index.
+    async fn fetch_commit(
+        &self,
+        index: usize,
+        block_height: u64,
+    ) -> Result<CommitResponse, TendermintRpcError> {
+        self.get_json(
+            index,
+            "commit",
+            &[
+                ("height", block_height.to_string()),
+                ("per_page", "100".to_string()), // helpful only when fetching validators
+            ],
+            Some(block_height),
+        )
+        .await
+    }
 
+    /// Fetches validators for a specific block height from the endpoint at the index. Every page is
+    /// fetched from the same endpoint.
+    async fn fetch_validators(
+        &self,
+        index: usize,
+        block_height: u64,
+    ) -> Result<Vec<Info>, TendermintRpcError> {
         let mut validators = vec![];
         let mut collected_validators = 0;
         let mut page_index = 1;
         loop {
-            let response = self
-                .client
-                .get(&url)
-                .query(&[
-                    ("height", block_height.to_string().as_str()),
-                    ("per_page", "100"),
-                    ("page", page_index.to_string().as_str()),
-                ])
-                .send()
-                .await?
-                .json::<ValidatorSetResponse>()
+            let response: ValidatorSetResponse = self
+                .get_json(
+                    index,
+                    "validators",
+                    &[
+                        ("height", block_height.to_string()),
+                        ("per_page", "100".to_string()),
+                        ("page", page_index.to_string()),
+                    ],
+                    Some(block_height),
+                )
                 .await?;
             let block_validator_set: BlockValidatorSet = response.result;
+            let invalid_count = || TendermintRpcError::InvalidResponse {
+                url: format!("{}/validators", self.urls[index]),
+                message: format!(
+                    "invalid validator count {} of {}",
+                    block_validator_set.count, block_validator_set.total
+                ),
+            };
+            let count = block_validator_set
+                .count
+                .parse::<i32>()
+                .map_err(|_| invalid_count())?;
+            let total = block_validator_set
+                .total
+                .parse::<i32>()
+                .map_err(|_| invalid_count())?;
             validators.extend(block_validator_set.validators);
-            collected_validators += block_validator_set.count.parse::<i32>().unwrap();
+            collected_validators += count;
 
-            if collected_validators >= block_validator_set.total.parse::<i32>().unwrap() {
+            if count == 0 || collected_validators >= total {
                 break;
             }
             page_index += 1;
@@ -269,20 +588,95 @@ impl TendermintRPCClient {
         Ok(validators)
     }
 
-    /// Fetches a light block for a specific block height and peer ID.
+    /// Fetches a light block for a specific block height and peer ID. With a quorum of one, the
+    /// whole light block is fetched from the first endpoint that serves it. With a quorum of more
+    /// than one, the light block is fetched from each endpoint in turn until quorum endpoints agree
+    /// on its header hash and validator sets.
     async fn fetch_light_block(
         &self,
         block_height: u64,
         peer_id: [u8; 20],
-    ) -> Result<LightBlock, Box<dyn Error>> {
-        let commit_response = self.fetch_commit(block_height).await?;
+    ) -> Result<LightBlock, TendermintRpcError> {
+        if self.quorum <= 1 {
+            return self
+                .with_failover(|index| self.fetch_light_block_from(index, block_height, peer_id))
+                .await;
+        }
+
+        // Distinct light blocks returned by the endpoints, with the number of endpoints that agree.
+        let mut candidates: Vec<(LightBlock, usize)> = Vec::new();
+        for index in 0..self.urls.len() {
+            let light_block = match self
+                .fetch_light_block_from(index, block_height, peer_id)
+                .await
+            {
+                Ok(light_block) => light_block,
+                Err(err) => {
+                    warn!(
+                        "Skipping {} for quorum after error: {}",
+                        self.urls[index], err
+                    );
+                    continue;
+                }
+            };
+            let position = candidates
+                .iter()
+                .position(|(candidate, _)| Self::light_blocks_agree(candidate, &light_block));
+            let position = match position {
+                Some(position) => {
+                    candidates[position].1 += 1;
+                    position
+                }
+                None => {
+                    candidates.push((light_block, 1));
+                    candidates.len() - 1
+                }
+            };
+            if candidates[position].1 >= self.quorum {
+                if candidates.len() > 1 {
+                    warn!(
+                        "Tendermint RPC endpoints disagree on block {}",
+                        block_height
+                    );
+                }
+                return Ok(candidates.swap_remove(position).0);
+            }
+        }
+
+        Err(TendermintRpcError::QuorumNotReached {
+            height: block_height,
+            quorum: self.quorum,
+            agreeing: candidates
+                .iter()
+                .map(|(_, count)| *count)
+                .max()
+                .unwrap_or(0),
+        })
+    }
+
+    /// Whether two light blocks have the same header hash and validator sets. Commits are not
+    /// compared, as endpoints may serve different sets of signatures for the same block.
+    fn light_blocks_agree(a: &LightBlock, b: &LightBlock) -> bool {
+        a.signed_header.header.hash() == b.signed_header.header.hash()
+            && a.validators.hash() == b.validators.hash()
+            && a.next_validators.hash() == b.next_validators.hash()
+    }
+
+    /// Fetches a light block for a specific block height and peer ID from the endpoint at the index.
+    async fn fetch_light_block_from(
+        &self,
+        index: usize,
+        block_height: u64,
+        peer_id: [u8; 20],
+    ) -> Result<LightBlock, TendermintRpcError> {
+        let commit_response = self.fetch_commit(index, block_height).await?;
         let mut signed_header = commit_response.result.signed_header;
 
-        let validator_response = self.fetch_validators(block_height).await?;
+        let validator_response = self.fetch_validators(index, block_height).await?;
 
         let validators = Set::new(validator_response, None);
 
-        let next_validator_response = self.fetch_validators(block_height + 1).await?;
+        let next_validator_response = self.fetch_validators(index, block_height + 1).await?;
         let next_validators = Set::new(next_validator_response, None);
 
         self.sort_signatures_by_validators_power_desc(&mut signed_header, &validators);
@@ -293,48 +687,82 @@ impl TendermintRPCClient {
             Id::new(peer_id),
         ))
     }
+}
+
+#[async_trait]
+impl LightBlockProvider for TendermintRPCClient {
+    async fn get_latest_block_height(&self) -> Result<u64, TendermintRpcError> {
+        let latest_commit = self.fetch_latest_commit().await?;
+        Ok(latest_commit.result.signed_header.header.height.value())
+    }
 
-    /// Determines if a valid skip is possible between start_block and target_block.
-    pub fn is_valid_skip(
-        start_validator_set: TendermintValidatorSet,
-        target_validator_set: TendermintValidatorSet,
-        target_block_commit: Commit,
-    ) -> bool {
-        let threshold = 1_f64 / 3_f64;
-        let mut shared_voting_power = 0_u64;
-        let target_block_total_voting_power = target_validator_set.total_voting_power().value();
-        let start_block_validators = start_validator_set.validators();
-        let mut start_block_idx = 0;
-        let start_block_num_validators = start_block_validators.len();
-
-        // Exit if we have already reached the threshold
-        while (target_block_total_voting_power as f64) * threshold > shared_voting_power as f64
-            && start_block_idx < start_block_num_validators
-        {
-            if let Some(target_block_validator) =
-                target_validator_set.validator(start_block_validators[start_block_idx].address)
-            {
-                // Confirm that the validator has signed on target_block.
-                for sig in target_block_commit.signatures.iter() {
-                    if let Some(validator_address) = sig.validator_address() {
-                        if validator_address == target_block_validator.address {
-                            // Add the shared voting power to the validator
-                            shared_voting_power += target_block_validator.power.value();
-                        }
-                    }
-                }
-            }
-            start_block_idx += 1;
+    async fn get_light_block(&self, height: u64) -> Result<LightBlock, TendermintRpcError> {
+        let peer_id = self.fetch_peer_id().await?;
+        self.fetch_light_block(height, peer_id).await
+    }
+
+    async fn get_headers_in_range(
+        &self,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<Vec<Header>, TendermintRpcError> {
+        self.fetch_headers_in_range(start_height, end_height).await
+    }
+
+    /// Fetches the validator set from the first endpoint that responds, without a quorum check.
+    async fn get_validators(&self, height: u64) -> Result<Set, TendermintRpcError> {
+        let validators = self
+            .with_failover(|index| self.fetch_validators(index, height))
+            .await?;
+        Ok(Set::new(validators, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default();
+        for retry in 0..10 {
+            let backoff = policy.backoff(retry, None);
+            assert!(backoff <= policy.max_backoff);
+            assert!(backoff <= policy.initial_backoff.saturating_mul(2u32.pow(retry)));
         }
+    }
 
-        (target_block_total_voting_power as f64) * threshold <= shared_voting_power as f64
+    #[test]
+    fn test_backoff_retry_after() {
+        let policy = RetryPolicy::default();
+
+        // Retry-After is honoured beyond the exponential backoff cap, up to its own cap.
+        let retry_after = policy.max_backoff * 4;
+        assert!(retry_after < policy.max_retry_after);
+        assert_eq!(policy.backoff(0, Some(retry_after)), retry_after);
+        assert_eq!(
+            policy.backoff(0, Some(policy.max_retry_after * 2)),
+            policy.max_retry_after
+        );
+        assert_eq!(policy.backoff(3, Some(Duration::ZERO)), Duration::ZERO);
     }
 
-    /// Fetches a header hash for a specific block height.
-    pub async fn fetch_header_hash(&self, block_height: u64) -> B256 {
-        let peer_id = self.fetch_peer_id().await.unwrap();
-        let light_block = self.fetch_light_block(block_height, peer_id).await.unwrap();
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 5 "), Some(Duration::from_secs(5)));
+        assert_eq!(parse_retry_after("soon"), None);
+        assert_eq!(parse_retry_after("-1"), None);
+
+        // An HTTP date in the past means retrying immediately.
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
 
-        B256::from_slice(light_block.signed_header.header.hash().as_bytes())
+        // An HTTP date in the future is the time until that date.
+        let date = chrono::Utc::now() + chrono::Duration::seconds(120);
+        let retry_after = parse_retry_after(&date.to_rfc2822()).unwrap();
+        assert!(retry_after > Duration::from_secs(110) && retry_after <= Duration::from_secs(120));
     }
 }